@@ -0,0 +1,51 @@
+//! Generates `Attributes` structs from sample documents and JSON Schema or OpenAPI files
+//!
+//! Usage: `json-api-codegen [--output FILE] INPUT...`
+
+use json_api::Codegen;
+use std::{env, process};
+
+const USAGE : &str = "Usage: json-api-codegen [--output FILE] INPUT...
+
+Reads sample JSON:API documents and JSON Schema or OpenAPI files, and writes Rust
+`Attributes` structs for every resource type found to FILE, or standard output";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut output = None;
+    let mut inputs = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = Some(path),
+                None => fail("--output requires a file"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            },
+            _ => inputs.push(arg),
+        }
+    }
+    if inputs.is_empty() {
+        fail("no inputs given");
+    }
+
+    let mut codegen = Codegen::new();
+    for input in &inputs {
+        if let Err(e) = codegen.add_file(input) {
+            fail(&format!("failed to read {} ({})", input, e));
+        }
+    }
+    match output {
+        Some(path) => if let Err(e) = codegen.write_to(&path) {
+            fail(&format!("failed to write {} ({})", path, e));
+        },
+        None => print!("{}", codegen.generate()),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("json-api-codegen: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,279 @@
+use crate::{
+    Document,
+    Error,
+    GenericObject,
+    Identifier,
+    JsonApi,
+    Link,
+    Links,
+    Map,
+    Meta,
+    OptionalVec,
+    Relationship,
+    Relationships,
+};
+use crate::object::map_to_storage;
+use serde::de::{Deserialize, Deserializer};
+use serde_derive::{Serialize, Deserialize};
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// A `Document` that borrows its ids, types and links from the input it was parsed from
+///
+/// Strings are only copied when they contain escape sequences, use `into_owned` to convert
+/// into a regular `Document` once the input is going away
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-top-level) for more information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct BorrowedDocument<'a> {
+    /// The document's primary data
+    #[serde(borrow, skip_serializing_if = "OptionalVec::is_not_present", default)]
+    pub data: OptionalVec<BorrowedObject<'a>>,
+    /// Any errors that were encountered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<Error>>,
+    /// Non-standard meta information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+    /// Information about the version of JSON:API being used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jsonapi: Option<JsonApi>,
+    /// Links related to the primary data
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub links: Option<BorrowedLinks<'a>>,
+    /// Included resources related to the primary data or each other
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub included: Option<Vec<BorrowedObject<'a>>>,
+}
+
+impl<'a> BorrowedDocument<'a> {
+    /// Copies everything still borrowed from the input into a regular `Document`
+    pub fn into_owned(self) -> Document {
+        Document {
+            data: match self.data {
+                OptionalVec::NotPresent => OptionalVec::NotPresent,
+                OptionalVec::One(one) => OptionalVec::One(one.map(BorrowedObject::into_owned)),
+                OptionalVec::Many(many) =>
+                    OptionalVec::Many(many.into_iter().map(BorrowedObject::into_owned).collect()),
+            },
+            errors: self.errors,
+            meta: self.meta,
+            jsonapi: self.jsonapi,
+            links: self.links.map(links_into_owned),
+            included: self.included
+                .map(|included| included.into_iter().map(BorrowedObject::into_owned).collect()),
+        }
+    }
+}
+
+/// A resource object that borrows from the input it was parsed from
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-objects)
+/// for more information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BorrowedObject<'a> {
+    /// The object's id, empty when a client is creating a resource without one
+    #[serde(borrow, skip_serializing_if = "str::is_empty", default)]
+    pub id: Cow<'a, str>,
+    /// The object's type, renamed here to kind due to keyword limitations
+    #[serde(borrow, rename = "type")]
+    pub kind: Cow<'a, str>,
+    /// The object's attributes, left untyped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<Map<String, Value>>,
+    /// Describes relationships between this resource and others
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub relationships: Option<Map<String, BorrowedRelationship<'a>>>,
+    /// Contains links relating to the resource
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub links: Option<BorrowedLinks<'a>>,
+    /// Non-standard meta information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl<'a> BorrowedObject<'a> {
+    /// Copies everything still borrowed from the input into a `GenericObject`
+    pub fn into_owned(self) -> GenericObject {
+        GenericObject {
+            id: self.id.into_owned(),
+            kind: self.kind.into_owned(),
+            attributes: self.attributes.map(map_to_storage),
+            relationships: self.relationships.map(|relationships| relationships.into_iter()
+                .map(|(k, r)| (k, r.into_owned()))
+                .collect::<Relationships>()),
+            links: self.links.map(links_into_owned),
+            meta: self.meta,
+        }
+    }
+}
+
+/// A relationship object that borrows from the input it was parsed from
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-object-relationships)
+/// for more information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct BorrowedRelationship<'a> {
+    /// MUST contain at least a `self` link or a `related` link
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub links: Option<BorrowedLinks<'a>>,
+    /// The objects this relationship links to
+    #[serde(borrow, skip_serializing_if = "OptionalVec::is_not_present", default)]
+    pub data: OptionalVec<BorrowedIdentifier<'a>>,
+    /// Non-standard meta information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl<'a> BorrowedRelationship<'a> {
+    /// Copies everything still borrowed from the input into a `Relationship`
+    pub fn into_owned(self) -> Relationship {
+        Relationship {
+            links: self.links.map(links_into_owned),
+            data: match self.data {
+                OptionalVec::NotPresent => OptionalVec::NotPresent,
+                OptionalVec::One(one) => OptionalVec::One(one.map(BorrowedIdentifier::into_owned)),
+                OptionalVec::Many(many) =>
+                    OptionalVec::Many(many.into_iter().map(BorrowedIdentifier::into_owned).collect()),
+            },
+            meta: self.meta,
+        }
+    }
+}
+
+/// A resource identifier object that borrows from the input it was parsed from
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-identifier-objects)
+/// for more information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BorrowedIdentifier<'a> {
+    /// The identifier of the object
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    /// The object's type, renamed here to kind due to keyword limitations
+    #[serde(borrow, rename = "type")]
+    pub kind: Cow<'a, str>,
+    /// Non-standard meta information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl<'a> BorrowedIdentifier<'a> {
+    /// Copies everything still borrowed from the input into an `Identifier`
+    pub fn into_owned(self) -> Identifier {
+        Identifier {
+            id: self.id.into_owned(),
+            kind: self.kind.into_owned(),
+            meta: self.meta,
+        }
+    }
+}
+
+/// A collection of links that borrow from the input they were parsed from
+pub type BorrowedLinks<'a> = Map<String, BorrowedLink<'a>>;
+
+/// A link object that borrows from the input it was parsed from
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-links) for more information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum BorrowedLink<'a> {
+    Url(#[serde(borrow)] Cow<'a, str>),
+    Object {
+        #[serde(borrow, deserialize_with = "borrow_option", skip_serializing_if = "Option::is_none", default)]
+        href: Option<Cow<'a, str>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        meta: Option<Meta>,
+    },
+}
+
+impl<'a> BorrowedLink<'a> {
+    /// Grabs the url of the link regardless of its variant
+    pub fn href(&self) -> Option<&str> {
+        match self {
+            BorrowedLink::Url(href) => Some(href),
+            BorrowedLink::Object{ href: Some(href), meta: _ } => Some(href),
+            _ => None,
+        }
+    }
+
+    /// Copies everything still borrowed from the input into a `Link`
+    pub fn into_owned(self) -> Link {
+        match self {
+            BorrowedLink::Url(href) => Link::Url(href.into_owned()),
+            BorrowedLink::Object{ href, meta } => Link::Object {
+                href: href.map(Cow::into_owned),
+                meta,
+            },
+        }
+    }
+}
+
+fn links_into_owned(links: BorrowedLinks) -> Links {
+    links.into_iter().map(|(k, l)| (k, l.into_owned())).collect()
+}
+
+/// Serde only borrows `Cow`s that aren't nested in another type, so this borrows the
+/// contents of an `Option` by hand
+fn borrow_option<'de, D>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error>
+where D: Deserializer<'de> {
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Borrowed>::deserialize(deserializer)?.map(|b| b.0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DOCUMENT : &str = include_str!("../tests/article.json");
+
+    #[test]
+    fn borrows() {
+        let d : BorrowedDocument = serde_json::from_str(DOCUMENT).unwrap();
+        let data = match &d.data {
+            OptionalVec::One(Some(data)) => data,
+            _ => panic!("expected a single resource"),
+        };
+        assert!(matches!(data.id, Cow::Borrowed("1")));
+        assert!(matches!(data.kind, Cow::Borrowed("articles")));
+        assert!(matches!(data.links.as_ref().unwrap()["self"], BorrowedLink::Url(Cow::Borrowed(_))));
+
+        let author = &data.relationships.as_ref().unwrap()["author"];
+        match &author.data {
+            OptionalVec::One(Some(id)) => assert!(matches!(id.id, Cow::Borrowed("9"))),
+            _ => panic!("expected a single identifier"),
+        }
+    }
+
+    #[test]
+    fn borrows_link_object() {
+        let l : BorrowedLink = serde_json::from_str("{\"href\":\"a\",\"meta\":{}}").unwrap();
+        match &l {
+            BorrowedLink::Object{ href: Some(href), meta: Some(_) } =>
+                assert!(matches!(href, Cow::Borrowed("a"))),
+            _ => panic!("expected a link object"),
+        }
+        assert_eq!(l.href(), Some("a"));
+    }
+
+    #[test]
+    fn escaped() {
+        let i : BorrowedIdentifier = serde_json::from_str("{\"id\":\"a\\nb\",\"type\":\"c\"}").unwrap();
+        assert_eq!(i.id, "a\nb");
+        assert!(matches!(i.id, Cow::Owned(_)));
+
+        let i : BorrowedIdentifier = serde_json::from_str("{\"id\":\"a\",\"type\":\"c\",\"lid\":\"x\"}").unwrap();
+        assert_eq!(i.into_owned(), Identifier::new("a".into(), "c".into()));
+    }
+
+    #[test]
+    fn into_owned() {
+        // Compared as values since raw attributes don't keep the input's formatting here
+        let borrowed : BorrowedDocument = serde_json::from_str(DOCUMENT).unwrap();
+        let owned : Document = serde_json::from_str(DOCUMENT).unwrap();
+        assert_eq!(serde_json::to_value(borrowed.into_owned()).unwrap(),
+            serde_json::to_value(owned).unwrap());
+    }
+}
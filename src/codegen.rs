@@ -0,0 +1,864 @@
+use crate::{
+    AttributeSchema,
+    AttributeType,
+    Cardinality,
+    Document,
+    GenericObject,
+    OptionalVec,
+    RelationshipSchema,
+    Schema,
+    TypeSchema,
+};
+use crate::object::storage_to_map;
+use serde_json::{Map as JsonMap, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    fs,
+    io,
+    path::Path,
+};
+
+/// Generates Rust `Attributes` structs for the resource types of an existing API
+///
+/// Resource types are collected into a `Schema` from sample documents, JSON Schema or
+/// OpenAPI files, or a `Schema` directly. `generate` then produces a struct for every type
+/// along with an `Attributes` implementation providing `kind`, `links` and `relationships`
+///
+/// The generated code refers to `json_api`, `serde_derive` and `serde_json` by their full
+/// paths, so it can be written from a build script and pulled in with `include!`
+///
+/// ```no_run
+/// // build.rs
+/// let mut codegen = json_api::Codegen::new();
+/// codegen.add_file("api/sample.json").unwrap();
+/// let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("resources.rs");
+/// codegen.write_to(out).unwrap();
+///
+/// // src/lib.rs
+/// // include!(concat!(env!("OUT_DIR"), "/resources.rs"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Codegen {
+    schema: Schema,
+    /// Attributes that have only been seen as `null` in sample documents, keyed by type and
+    /// name, whose `AttributeType` is a placeholder
+    untyped: BTreeSet<(String, String)>,
+    /// Link templates inferred from sample documents, keyed by type and link name, with
+    /// `{id}` standing in for the resource's id
+    links: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Codegen {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The resource types collected so far
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Collects the resource types of the primary data and included resources of a sample
+    /// document
+    ///
+    /// Attributes are typed after their values, and are optional if they were `null` or left
+    /// out of any resource of their type. Links containing a resource's id become templates
+    /// for the generated `links` function
+    pub fn add_document(&mut self, document: &Document) {
+        let data = match &document.data {
+            OptionalVec::One(Some(go)) => std::slice::from_ref(go),
+            OptionalVec::Many(many) => many.as_slice(),
+            _ => &[],
+        };
+        for go in data.iter().chain(document.included.iter().flatten()) {
+            self.add_sample(go);
+        }
+    }
+
+    /// Collects the resource types described by a JSON Schema or OpenAPI document
+    ///
+    /// Any schema for an object with a `type` property holding a `const` and an `attributes`
+    /// or `relationships` property is taken to describe resources of that type, wherever it
+    /// appears. Attributes are optional unless they are `required`, and relationships are
+    /// to-many when their `data` is an array
+    pub fn add_json_schema(&mut self, root: &Value) {
+        let mut resources = Vec::new();
+        find_resources(root, &mut resources);
+        for resource in resources {
+            let (kind, t) = type_from_json_schema(root, resource);
+            merge_type(self.schema.types.entry(kind).or_default(), t);
+        }
+    }
+
+    /// Collects the resource types of a `Schema`
+    pub fn add_schema(&mut self, schema: &Schema) {
+        for (kind, t) in &schema.types {
+            merge_type(self.schema.types.entry(kind.clone()).or_default(), t.clone());
+        }
+    }
+
+    /// Reads a file holding either a sample document or a JSON Schema or OpenAPI document
+    ///
+    /// Files with a top-level `openapi`, `$schema`, `$defs`, `definitions`, `components` or
+    /// `properties` member are read as schemas
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let value : Value = serde_json::from_slice(&fs::read(path)?)?;
+        let is_schema = ["openapi", "$schema", "$defs", "definitions", "components", "properties"]
+            .iter()
+            .any(|k| value.get(k).is_some());
+        if is_schema {
+            self.add_json_schema(&value);
+        } else {
+            self.add_document(&serde_json::from_value(value)?);
+        }
+        Ok(())
+    }
+
+    /// Generates the Rust source for every resource type collected, in order of their `type`
+    ///
+    /// Types or attributes whose names only differ in punctuation or case, like `blog-posts`
+    /// and `blog_posts`, would get the same Rust name, so later ones get a numbered suffix.
+    /// So do types named after prelude items the generated code uses, like `Option` or `Self`
+    pub fn generate(&self) -> String {
+        let mut kinds : Vec<_> = self.schema.types.keys().collect();
+        kinds.sort();
+
+        let mut s = String::from("// Generated by json-api codegen, do not edit\n");
+        let mut names : BTreeSet<_> = RESERVED_TYPES.iter().map(|n| n.to_string()).collect();
+        for kind in kinds {
+            let name = unique(type_name(kind), "", &mut names);
+            s.push('\n');
+            write_type(&mut s, &name, kind, &self.schema.types[kind], self.links.get(kind));
+        }
+        s
+    }
+
+    /// Writes the generated source to a file, only touching it if the contents changed so
+    /// build scripts don't cause needless rebuilds
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let source = self.generate();
+        if fs::read_to_string(path.as_ref()).ok().as_deref() != Some(source.as_str()) {
+            fs::write(path, source)?;
+        }
+        Ok(())
+    }
+
+    fn add_sample(&mut self, go: &GenericObject) {
+        let first = !self.schema.types.contains_key(&go.kind);
+        let t = self.schema.types.entry(go.kind.clone()).or_default();
+
+        let attributes = go.attributes.as_ref()
+            .and_then(|a| storage_to_map(a).ok())
+            .unwrap_or_default();
+        for (name, a) in t.attributes.iter_mut() {
+            if !attributes.contains_key(name) {
+                a.nullable = true;
+            }
+        }
+        for (name, value) in attributes {
+            let key = (go.kind.clone(), name.clone());
+            let kind = infer_type(&value);
+            match (t.attributes.get_mut(&name), kind) {
+                (Some(a), Some(kind)) => {
+                    a.kind = if self.untyped.remove(&key) { kind } else { merge_kind(a.kind, kind) };
+                },
+                (Some(a), None) => a.nullable = true,
+                (None, kind) => {
+                    if kind.is_none() {
+                        self.untyped.insert(key);
+                    }
+                    t.attributes.insert(name, AttributeSchema {
+                        kind: kind.unwrap_or(AttributeType::Any),
+                        nullable: kind.is_none() || !first,
+                    });
+                },
+            }
+        }
+
+        for (name, r) in go.relationships.iter().flatten() {
+            let (cardinality, targets) = match &r.data {
+                OptionalVec::One(one) => (Some(Cardinality::ToOne), one.iter().collect::<Vec<_>>()),
+                OptionalVec::Many(many) => (Some(Cardinality::ToMany), many.iter().collect()),
+                OptionalVec::NotPresent => (None, Vec::new()),
+            };
+            let schema = t.relationships.entry(name.clone()).or_insert(RelationshipSchema {
+                targets: Vec::new(),
+                cardinality: cardinality.unwrap_or(Cardinality::ToOne),
+            });
+            if let Some(cardinality) = cardinality {
+                schema.cardinality = cardinality;
+            }
+            for id in targets {
+                if !schema.targets.contains(&id.kind) {
+                    schema.targets.push(id.kind.clone());
+                }
+            }
+        }
+
+        if !go.id.is_empty() {
+            for (name, link) in go.links.iter().flatten() {
+                if let Some(template) = link.href().and_then(|href| link_template(href, &go.id)) {
+                    self.links.entry(go.kind.clone()).or_default().entry(name.clone()).or_insert(template);
+                }
+            }
+        }
+    }
+}
+
+fn infer_type(value: &Value) -> Option<AttributeType> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(AttributeType::Boolean),
+        Value::Number(n) if n.is_f64() => Some(AttributeType::Number),
+        Value::Number(_) => Some(AttributeType::Integer),
+        Value::String(_) => Some(AttributeType::String),
+        Value::Array(_) => Some(AttributeType::Array),
+        Value::Object(_) => Some(AttributeType::Object),
+    }
+}
+
+fn merge_kind(a: AttributeType, b: AttributeType) -> AttributeType {
+    use AttributeType::{Any, Integer, Number};
+    match (a, b) {
+        (a, b) if a == b => a,
+        (Integer, Number) | (Number, Integer) => Number,
+        _ => Any,
+    }
+}
+
+fn merge_type(t: &mut TypeSchema, other: TypeSchema) {
+    for (name, a) in other.attributes {
+        match t.attributes.get_mut(&name) {
+            Some(existing) => {
+                existing.kind = merge_kind(existing.kind, a.kind);
+                existing.nullable |= a.nullable;
+            },
+            None => {
+                t.attributes.insert(name, a);
+            },
+        }
+    }
+    for (name, r) in other.relationships {
+        match t.relationships.get_mut(&name) {
+            Some(existing) => for target in r.targets {
+                if !existing.targets.contains(&target) {
+                    existing.targets.push(target);
+                }
+            },
+            None => {
+                t.relationships.insert(name, r);
+            },
+        }
+    }
+}
+
+/// Replaces the path segments of `href` equal to `id` with `{id}`
+fn link_template(href: &str, id: &str) -> Option<String> {
+    let segments : Vec<_> = href.split('/')
+        .map(|segment| if segment == id { "{id}" } else { segment })
+        .collect();
+    if segments.contains(&"{id}") { Some(segments.join("/")) } else { None }
+}
+
+fn find_resources<'a>(value: &'a Value, found: &mut Vec<&'a JsonMap<String, Value>>) {
+    match value {
+        Value::Object(o) => {
+            if resource_kind(o).is_some() {
+                found.push(o);
+            }
+            for v in o.values() {
+                find_resources(v, found);
+            }
+        },
+        Value::Array(a) => for v in a {
+            find_resources(v, found);
+        },
+        _ => (),
+    }
+}
+
+fn resource_kind(schema: &JsonMap<String, Value>) -> Option<&str> {
+    let properties = schema.get("properties")?;
+    if properties.get("attributes").is_none() && properties.get("relationships").is_none() {
+        return None;
+    }
+    properties.get("type")?.get("const")?.as_str()
+}
+
+/// Follows a local `$ref`, if there is one
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    let mut schema = schema;
+    // Bounded in case of a reference cycle
+    for _ in 0..16 {
+        match schema.get("$ref").and_then(Value::as_str) {
+            Some(r) if r.starts_with('#') => match root.pointer(&r[1..]) {
+                Some(target) => schema = target,
+                None => break,
+            },
+            _ => break,
+        }
+    }
+    schema
+}
+
+fn type_from_json_schema(root: &Value, resource: &JsonMap<String, Value>) -> (String, TypeSchema) {
+    let kind = resource_kind(resource).unwrap_or_default().to_string();
+    let mut t = TypeSchema::default();
+    let properties = &resource["properties"];
+
+    if let Some(attributes) = properties.get("attributes").map(|a| resolve(root, a)) {
+        let required : Vec<&str> = attributes.get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        for (name, schema) in attributes.get("properties").and_then(Value::as_object).into_iter().flatten() {
+            let mut nullable = !required.contains(&name.as_str());
+            let kind = json_type(root, schema, &mut nullable, &mut Vec::new());
+            t.attributes.insert(name.clone(), AttributeSchema { kind, nullable });
+        }
+    }
+
+    if let Some(relationships) = properties.get("relationships").map(|r| resolve(root, r)) {
+        for (name, schema) in relationships.get("properties").and_then(Value::as_object).into_iter().flatten() {
+            let data = resolve(root, schema).pointer("/properties/data").map(|d| resolve(root, d));
+            let to_many = data.is_some_and(|d| d.get("type") == Some(&"array".into()) || d.get("items").is_some());
+            let mut targets = Vec::new();
+            if let Some(data) = data {
+                collect_targets(root, data, &mut targets, 0);
+            }
+            t.relationships.insert(name.clone(), RelationshipSchema {
+                targets,
+                cardinality: if to_many { Cardinality::ToMany } else { Cardinality::ToOne },
+            });
+        }
+    }
+
+    (kind, t)
+}
+
+/// `refs` holds the `$ref`s being followed, a schema that refers back to itself is `Any`
+fn json_type<'a>(root: &'a Value, schema: &'a Value, nullable: &mut bool, refs: &mut Vec<&'a str>)
+    -> AttributeType {
+    let reference = schema.get("$ref").and_then(Value::as_str);
+    if let Some(r) = reference {
+        if refs.contains(&r) {
+            return AttributeType::Any;
+        }
+        refs.push(r);
+    }
+    let kind = json_type_resolved(root, resolve(root, schema), nullable, refs);
+    if reference.is_some() {
+        refs.pop();
+    }
+    kind
+}
+
+fn json_type_resolved<'a>(root: &'a Value, schema: &'a Value, nullable: &mut bool,
+    refs: &mut Vec<&'a str>) -> AttributeType {
+    let single = |t: &str| match t {
+        "string" => AttributeType::String,
+        "integer" => AttributeType::Integer,
+        "number" => AttributeType::Number,
+        "boolean" => AttributeType::Boolean,
+        "array" => AttributeType::Array,
+        "object" => AttributeType::Object,
+        _ => AttributeType::Any,
+    };
+    let combine = |kinds: Vec<AttributeType>| match kinds.split_first() {
+        Some((first, rest)) if rest.iter().all(|k| k == first) => *first,
+        _ => AttributeType::Any,
+    };
+
+    match schema.get("type") {
+        Some(Value::String(t)) if t == "null" => {
+            *nullable = true;
+            return AttributeType::Any;
+        },
+        Some(Value::String(t)) => return single(t),
+        Some(Value::Array(types)) => {
+            let types : Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            if types.contains(&"null") {
+                *nullable = true;
+            }
+            return combine(types.into_iter().filter(|t| *t != "null").map(single).collect());
+        },
+        _ => (),
+    }
+    for key in &["oneOf", "anyOf"] {
+        if let Some(Value::Array(variants)) = schema.get(*key) {
+            let mut kinds = Vec::new();
+            for variant in variants {
+                if resolve(root, variant).get("type") == Some(&"null".into()) {
+                    *nullable = true;
+                } else {
+                    kinds.push(json_type(root, variant, nullable, refs));
+                }
+            }
+            return combine(kinds);
+        }
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return combine(values.iter().map(|v| infer_type(v).unwrap_or(AttributeType::Any)).collect());
+    }
+    if let Some(value) = schema.get("const") {
+        return infer_type(value).unwrap_or(AttributeType::Any);
+    }
+    if schema.get("properties").is_some() {
+        return AttributeType::Object;
+    }
+    AttributeType::Any
+}
+
+/// Collects the `const` types of the identifiers a relationship's `data` may hold
+fn collect_targets(root: &Value, schema: &Value, targets: &mut Vec<String>, depth: usize) {
+    let schema = resolve(root, schema);
+    if depth > 4 {
+        return;
+    }
+    if let Some(kind) = schema.pointer("/properties/type/const").and_then(Value::as_str) {
+        if !targets.iter().any(|t| t == kind) {
+            targets.push(kind.into());
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        collect_targets(root, items, targets, depth + 1);
+    }
+    for key in &["oneOf", "anyOf"] {
+        for variant in schema.get(*key).and_then(Value::as_array).into_iter().flatten() {
+            collect_targets(root, variant, targets, depth + 1);
+        }
+    }
+}
+
+fn write_type(s: &mut String, name: &str, kind: &str, t: &TypeSchema,
+    links: Option<&BTreeMap<String, String>>) {
+    let mut attributes : Vec<_> = t.attributes.iter().collect();
+    attributes.sort_by(|a, b| a.0.cmp(b.0));
+    let mut relationships : Vec<_> = t.relationships.iter().collect();
+    relationships.sort_by(|a, b| a.0.cmp(b.0));
+    let links : Vec<_> = links.into_iter().flatten().collect();
+
+    // Writing to a `String` can't fail
+    let _ = writeln!(s, "/// Attributes of `{}` resources", kind);
+    s.push_str("#[derive(::serde_derive::Serialize, ::serde_derive::Deserialize, Debug, Clone, PartialEq)]\n");
+    if attributes.is_empty() {
+        let _ = writeln!(s, "pub struct {} {{}}", name);
+    } else {
+        let _ = writeln!(s, "pub struct {} {{", name);
+        let mut fields = BTreeSet::new();
+        for (attribute, schema) in attributes {
+            let field = unique(field_name(attribute), "_", &mut fields);
+            write_field(s, &field, attribute, schema);
+        }
+        s.push_str("}\n");
+    }
+
+    let _ = writeln!(s, "\nimpl ::json_api::Attributes for {} {{", name);
+    s.push_str("    type Id = String;\n\n");
+    s.push_str("    fn kind() -> String {\n");
+    let _ = writeln!(s, "        {:?}.into()", kind);
+    s.push_str("    }\n");
+    if !links.is_empty() {
+        s.push_str("\n    fn links(id: &str) -> Option<::json_api::Links> {\n");
+        s.push_str("        let mut links = ::json_api::Links::new();\n");
+        for (link, template) in links {
+            let format = template.replace('{', "{{").replace('}', "}}").replace("{{id}}", "{}");
+            let _ = writeln!(s, "        links.insert({:?}.into(), ::json_api::Link::Url(format!({:?}, id)));",
+                link, format);
+        }
+        s.push_str("        Some(links)\n");
+        s.push_str("    }\n");
+    }
+    if !relationships.is_empty() {
+        s.push_str("\n    fn relationships() -> Vec<(String, ::json_api::RelationshipSchema)> {\n");
+        s.push_str("        vec![\n");
+        for (relationship, schema) in relationships {
+            let _ = writeln!(s, "            ({:?}.into(), {}),", relationship, relationship_schema(schema));
+        }
+        s.push_str("        ]\n");
+        s.push_str("    }\n");
+    }
+    s.push_str("}\n");
+}
+
+/// The expression building `schema`, using its shorthands when there is a single target
+fn relationship_schema(schema: &RelationshipSchema) -> String {
+    match (&schema.targets[..], schema.cardinality) {
+        ([target], Cardinality::ToOne) => format!("::json_api::RelationshipSchema::to_one({:?}.into())", target),
+        ([target], Cardinality::ToMany) => format!("::json_api::RelationshipSchema::to_many({:?}.into())", target),
+        (targets, cardinality) => format!(
+            "::json_api::RelationshipSchema {{ targets: vec![{}], cardinality: ::json_api::Cardinality::{:?} }}",
+            targets.iter().map(|t| format!("{:?}.into()", t)).collect::<Vec<_>>().join(", "),
+            cardinality),
+    }
+}
+
+fn write_field(s: &mut String, field: &str, attribute: &str, schema: &AttributeSchema) {
+    let rust_type = match schema.kind {
+        AttributeType::String => "String",
+        AttributeType::Integer => "i64",
+        AttributeType::Number => "f64",
+        AttributeType::Boolean => "bool",
+        AttributeType::Array => "Vec<::serde_json::Value>",
+        AttributeType::Object => "::serde_json::Map<String, ::serde_json::Value>",
+        AttributeType::Any => "::serde_json::Value",
+    };
+
+    let mut serde = Vec::new();
+    if field.trim_start_matches("r#") != attribute {
+        serde.push(format!("rename = {:?}", attribute));
+    }
+    let rust_type = if schema.kind == AttributeType::Any {
+        if schema.nullable {
+            serde.push("default".to_string());
+        }
+        rust_type.to_string()
+    } else if schema.nullable {
+        serde.push("default".to_string());
+        serde.push("skip_serializing_if = \"Option::is_none\"".to_string());
+        format!("Option<{}>", rust_type)
+    } else {
+        rust_type.to_string()
+    };
+
+    if !serde.is_empty() {
+        let _ = writeln!(s, "    #[serde({})]", serde.join(", "));
+    }
+    let _ = writeln!(s, "    pub {}: {},", field, rust_type);
+}
+
+/// Adds a numbered suffix to `name` until it isn't `taken`
+fn unique(name: String, separator: &str, taken: &mut BTreeSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut n = 2;
+    while !taken.insert(unique.clone()) {
+        unique = format!("{}{}{}", name, separator, n);
+        n += 1;
+    }
+    unique
+}
+
+/// Converts a resource type into the name of a struct, so `blog-posts` becomes `BlogPosts`
+fn type_name(kind: &str) -> String {
+    let mut name : String = kind.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect::<String>()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'T');
+    }
+    name
+}
+
+/// Names a struct can't take without breaking the generated code, which relies on the prelude
+const RESERVED_TYPES : &[&str] = &[
+    "Self", "Option", "Some", "None", "Result", "Ok", "Err", "String", "Vec", "Box", "Clone",
+    "Copy", "Debug", "Default", "Drop", "Eq", "Fn", "FnMut", "FnOnce", "From", "Into",
+    "IntoIterator", "Iterator", "Ord", "PartialEq", "PartialOrd", "Send", "Sized", "Sync",
+    "ToOwned", "ToString", "TryFrom", "TryInto",
+];
+
+const KEYWORDS : &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "try", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Converts an attribute name into the name of a field, so `firstName` and `first-name` both
+/// become `first_name`
+fn field_name(attribute: &str) -> String {
+    let mut name = String::new();
+    let mut previous_lower = false;
+    for c in attribute.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            name.push(c);
+        } else {
+            name.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    match name.as_str() {
+        "_" | "self" | "super" | "crate" => format!("{}_", name),
+        n if KEYWORDS.contains(&n) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    const DOCUMENT : &str = include_str!("../tests/article.json");
+
+    fn attribute(kind: AttributeType, nullable: bool) -> AttributeSchema {
+        AttributeSchema { kind, nullable }
+    }
+
+    #[test]
+    fn infer_document() {
+        let mut codegen = Codegen::new();
+        codegen.add_document(&serde_json::from_str(DOCUMENT).unwrap());
+        let types = &codegen.schema().types;
+
+        assert_eq!(types["people"].attributes["first-name"], attribute(AttributeType::String, false));
+        assert_eq!(types["comments"].relationships["author"], RelationshipSchema::to_one("people".into()));
+        assert_eq!(types["articles"].relationships["comments"],
+            RelationshipSchema::to_many("comments".into()));
+        assert_eq!(codegen.links["articles"]["self"], "/articles/{id}");
+        assert_eq!(codegen.links["comments"]["self"], "/comments/{id}");
+    }
+
+    #[test]
+    fn infer_attributes() {
+        let document : Document = serde_json::from_value(json!({
+            "data": [
+                {"type": "a", "id": "1", "attributes": {"n": 1, "s": "s", "x": null, "m": 1}},
+                {"type": "a", "id": "2", "attributes": {"n": 1.5, "x": true, "m": "m", "late": []}},
+            ],
+        })).unwrap();
+        let mut codegen = Codegen::new();
+        codegen.add_document(&document);
+        let attributes = &codegen.schema().types["a"].attributes;
+
+        assert_eq!(attributes["n"], attribute(AttributeType::Number, false));
+        assert_eq!(attributes["s"], attribute(AttributeType::String, true));
+        assert_eq!(attributes["x"], attribute(AttributeType::Boolean, true));
+        assert_eq!(attributes["m"], attribute(AttributeType::Any, false));
+        assert_eq!(attributes["late"], attribute(AttributeType::Array, true));
+    }
+
+    #[test]
+    fn self_referential_json_schema() {
+        let schema = json!({
+            "$defs": {
+                "A": {"oneOf": [{"$ref": "#/$defs/A"}, {"type": "string"}]},
+                "B": {"anyOf": [{"$ref": "#/$defs/C"}, {"$ref": "#/$defs/C"}]},
+                "C": {"type": "integer"},
+                "Resource": {
+                    "properties": {
+                        "type": {"const": "things"},
+                        "attributes": {
+                            "properties": {
+                                "a": {"$ref": "#/$defs/A"},
+                                "b": {"$ref": "#/$defs/B"},
+                            },
+                        },
+                    },
+                },
+            },
+        });
+        let mut codegen = Codegen::new();
+        codegen.add_json_schema(&schema);
+        let t = &codegen.schema().types["things"];
+        assert_eq!(t.attributes["a"].kind, AttributeType::Any);
+        assert_eq!(t.attributes["b"].kind, AttributeType::Integer);
+    }
+
+    #[test]
+    fn infer_json_schema() {
+        let schema = json!({
+            "components": {
+                "schemas": {
+                    "Article": {
+                        "type": "object",
+                        "required": ["title"],
+                        "properties": {
+                            "title": {"type": "string"},
+                            "views": {"type": ["integer", "null"]},
+                            "status": {"$ref": "#/components/schemas/Status"},
+                        },
+                    },
+                    "Status": {"type": "string", "enum": ["draft", "published"]},
+                    "articles.Resource": {
+                        "type": "object",
+                        "properties": {
+                            "type": {"const": "articles"},
+                            "attributes": {"$ref": "#/components/schemas/Article"},
+                            "relationships": {
+                                "properties": {
+                                    "tags": {
+                                        "properties": {
+                                            "data": {
+                                                "type": "array",
+                                                "items": {"properties": {"type": {"const": "tags"}}},
+                                            },
+                                        },
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        });
+        let mut codegen = Codegen::new();
+        codegen.add_json_schema(&schema);
+        let t = &codegen.schema().types["articles"];
+
+        assert_eq!(t.attributes["title"], attribute(AttributeType::String, false));
+        assert_eq!(t.attributes["views"], attribute(AttributeType::Integer, true));
+        assert_eq!(t.attributes["status"], attribute(AttributeType::String, true));
+        assert_eq!(t.relationships["tags"], RelationshipSchema::to_many("tags".into()));
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn round_trip_json_schema() {
+        use crate::{Attributes, resource_document_schema};
+        use schemars::JsonSchema;
+        use serde_derive::{Serialize, Deserialize};
+
+        #[derive(Serialize, Deserialize, JsonSchema)]
+        struct Article {
+            title: String,
+            views: Option<u32>,
+        }
+
+        impl Attributes for Article {
+            type Id = String;
+            fn kind() -> String { "articles".into() }
+            fn relationships() -> Vec<(String, RelationshipSchema)> {
+                vec![("author".into(), RelationshipSchema::to_one("people".into()))]
+            }
+        }
+
+        let mut codegen = Codegen::new();
+        codegen.add_json_schema(resource_document_schema::<Article>().as_value());
+        let t = &codegen.schema().types["articles"];
+        assert_eq!(t.attributes["title"], attribute(AttributeType::String, false));
+        assert_eq!(t.attributes["views"], attribute(AttributeType::Integer, true));
+        assert_eq!(t.relationships["author"], RelationshipSchema::to_one("people".into()));
+        assert_eq!(codegen.schema().types.len(), 1);
+    }
+
+    #[test]
+    fn collisions() {
+        let mut t = TypeSchema::default();
+        t.attributes.insert("firstName".into(), attribute(AttributeType::String, false));
+        t.attributes.insert("first-name".into(), attribute(AttributeType::String, false));
+        let mut schema = Schema::new();
+        schema.types.insert("blog-posts".into(), t);
+        schema.types.insert("blog_posts".into(), TypeSchema::default());
+
+        let mut codegen = Codegen::new();
+        codegen.add_schema(&schema);
+        let source = codegen.generate();
+        let lines : Vec<_> = source.lines().map(str::trim).collect();
+        assert!(lines.contains(&"pub struct BlogPosts {"));
+        assert!(lines.contains(&"pub struct BlogPosts2 {}"));
+        assert!(lines.contains(&"impl ::json_api::Attributes for BlogPosts2 {"));
+        assert!(lines.contains(&"#[serde(rename = \"first-name\")]"));
+        assert!(lines.contains(&"pub first_name: String,"));
+        assert!(lines.contains(&"#[serde(rename = \"firstName\")]"));
+        assert!(lines.contains(&"pub first_name_2: String,"));
+    }
+
+    #[test]
+    fn generate() {
+        let mut t = TypeSchema::default();
+        t.attributes.insert("type".into(), attribute(AttributeType::String, false));
+        t.attributes.insert("firstName".into(), attribute(AttributeType::Any, true));
+        t.attributes.insert("age".into(), attribute(AttributeType::Integer, true));
+        t.relationships.insert("labels".into(), RelationshipSchema {
+            targets: vec!["tags".into(), "categories".into()],
+            cardinality: Cardinality::ToMany,
+        });
+        let mut schema = Schema::new();
+        schema.types.insert("blog-posts".into(), t);
+        schema.types.insert("tags".into(), TypeSchema::default());
+
+        let mut codegen = Codegen::new();
+        codegen.add_schema(&schema);
+        codegen.links.entry("blog-posts".into()).or_default().insert("self".into(), "/{a}/{id}".into());
+        assert_eq!(codegen.generate(), concat!(
+            "// Generated by json-api codegen, do not edit\n",
+            "\n",
+            "/// Attributes of `blog-posts` resources\n",
+            "#[derive(::serde_derive::Serialize, ::serde_derive::Deserialize, Debug, Clone, PartialEq)]\n",
+            "pub struct BlogPosts {\n",
+            "    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n",
+            "    pub age: Option<i64>,\n",
+            "    #[serde(rename = \"firstName\", default)]\n",
+            "    pub first_name: ::serde_json::Value,\n",
+            "    pub r#type: String,\n",
+            "}\n",
+            "\n",
+            "impl ::json_api::Attributes for BlogPosts {\n",
+            "    type Id = String;\n",
+            "\n",
+            "    fn kind() -> String {\n",
+            "        \"blog-posts\".into()\n",
+            "    }\n",
+            "\n",
+            "    fn links(id: &str) -> Option<::json_api::Links> {\n",
+            "        let mut links = ::json_api::Links::new();\n",
+            "        links.insert(\"self\".into(), ::json_api::Link::Url(format!(\"/{{a}}/{}\", id)));\n",
+            "        Some(links)\n",
+            "    }\n",
+            "\n",
+            "    fn relationships() -> Vec<(String, ::json_api::RelationshipSchema)> {\n",
+            "        vec![\n",
+            "            (\"labels\".into(), ::json_api::RelationshipSchema { targets: vec![\"tags\".into(), ",
+            "\"categories\".into()], cardinality: ::json_api::Cardinality::ToMany }),\n",
+            "        ]\n",
+            "    }\n",
+            "}\n",
+            "\n",
+            "/// Attributes of `tags` resources\n",
+            "#[derive(::serde_derive::Serialize, ::serde_derive::Deserialize, Debug, Clone, PartialEq)]\n",
+            "pub struct Tags {}\n",
+            "\n",
+            "impl ::json_api::Attributes for Tags {\n",
+            "    type Id = String;\n",
+            "\n",
+            "    fn kind() -> String {\n",
+            "        \"tags\".into()\n",
+            "    }\n",
+            "}\n",
+        ));
+    }
+
+    #[test]
+    fn reserved_names() {
+        let mut schema = Schema::new();
+        for kind in &["self", "option", "string", "strings", "vec", "box"] {
+            schema.types.insert(kind.to_string(), TypeSchema::default());
+        }
+        let mut codegen = Codegen::new();
+        codegen.add_schema(&schema);
+        let s = codegen.generate();
+        for name in &["Self2", "Option2", "String2", "Strings", "Vec2", "Box2"] {
+            assert!(s.contains(&format!("pub struct {} {{}}", name)), "missing {} in {}", name, s);
+        }
+        assert!(!s.contains("pub struct Self {}"));
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(type_name("blog-posts"), "BlogPosts");
+        assert_eq!(type_name("2fa_codes"), "T2faCodes");
+        assert_eq!(field_name("first-name"), "first_name");
+        assert_eq!(field_name("createdAt"), "created_at");
+        assert_eq!(field_name("HTTPCode"), "httpcode");
+        assert_eq!(field_name("match"), "r#match");
+        assert_eq!(field_name("self"), "self_");
+        assert_eq!(field_name("1st"), "_1st");
+        assert_eq!(link_template("https://a.b/people/9/x", "9"), Some("https://a.b/people/{id}/x".into()));
+        assert_eq!(link_template("/people", "9"), None);
+    }
+}
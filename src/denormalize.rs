@@ -0,0 +1,413 @@
+use crate::{
+    Document,
+    Error,
+    GenericObject,
+    Identifier,
+    IdentifierMap,
+    Map,
+    OptionalVec,
+    Relationship,
+    Relationships,
+    Schema,
+    Cardinality,
+};
+use crate::object::{map_to_storage, storage_to_map};
+use crate::validation::{bad_request, escape, finish};
+use serde_json::{Map as JsonMap, Value};
+
+/// What `Document::denormalize_with` does when a relationship leads back to a resource that
+/// is already being inlined further up the tree
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Cycles {
+    /// Write just the `type` and `id` of the resource
+    #[default]
+    Identifier,
+    /// Leave the resource out, dropping a to-one relationship member entirely
+    Omit,
+    /// Inline the resource again, until the depth limit is reached
+    Repeat,
+}
+
+impl Document {
+    /// Turns the primary data into a plain nested JSON tree, inlining related resources found
+    /// in the primary data or `included`
+    ///
+    /// See `denormalize_with`, cycles are broken by writing the resource's `type` and `id`
+    pub fn denormalize(&self, depth: usize) -> Value {
+        self.denormalize_with(depth, Cycles::default())
+    }
+
+    /// Turns the primary data into a plain nested JSON tree, inlining related resources found
+    /// in the primary data or `included`
+    ///
+    /// Every resource becomes an object holding its `type`, `id` and attributes, with each
+    /// relationship that has data replaced by the related resources. `depth` is how many
+    /// levels of relationships are inlined, past that or for resources the document doesn't
+    /// contain only the `type` and `id` are written. Links and meta are left out
+    ///
+    /// Produces `null` when the document has no primary data
+    pub fn denormalize_with(&self, depth: usize, cycles: Cycles) -> Value {
+        let mut denormalizer = Denormalizer {
+            index: self.index(),
+            cycles,
+            path: Vec::new(),
+        };
+        match &self.data {
+            OptionalVec::One(Some(go)) => denormalizer.resource(go, depth),
+            OptionalVec::Many(many) => many.iter().map(|go| denormalizer.resource(go, depth)).collect(),
+            _ => Value::Null,
+        }
+    }
+
+    /// Turns a nested JSON tree, like the one `denormalize` produces, back into a compound
+    /// document
+    ///
+    /// `value` is a resource of type `kind`, an array of them or `null`. The `schema` tells
+    /// which members are relationships and, when a nested resource has no `type` member, what
+    /// its type is from the relationship's single target. Every other member except `type`
+    /// and `id` is an attribute
+    ///
+    /// Nested resources with more than a `type` and `id` are moved to `included`, once each.
+    /// Only the primary data may leave out its `id`, which is then left empty. Moving the data
+    /// into a `CreateDocument` leaves it out again when serializing
+    ///
+    /// Produces `400 Bad Request` errors pointing into `value`
+    pub fn normalize(value: &Value, kind: &str, schema: &Schema) -> Result<Document, Vec<Error>> {
+        let mut normalizer = Normalizer {
+            schema,
+            included: Vec::new(),
+            errors: Vec::new(),
+        };
+        let data = match value {
+            Value::Null => OptionalVec::One(None),
+            Value::Array(many) => OptionalVec::Many(many.iter()
+                .enumerate()
+                .filter_map(|(i, v)| normalizer.resource(v, Some(kind), &format!("/{}", i), true))
+                .collect()),
+            one => OptionalVec::One(normalizer.resource(one, Some(kind), "", true)),
+        };
+        let Normalizer { included, errors, .. } = normalizer;
+        finish(errors)?;
+
+        let mut document = Document {
+            data,
+            included: if included.is_empty() { None } else { Some(included) },
+            ..Default::default()
+        };
+        document.dedup_included();
+        Ok(document)
+    }
+}
+
+struct Denormalizer<'a> {
+    index: IdentifierMap<&'a GenericObject>,
+    cycles: Cycles,
+    /// The resources currently being inlined, outermost first
+    path: Vec<Identifier>,
+}
+
+impl<'a> Denormalizer<'a> {
+    fn resource(&mut self, go: &GenericObject, depth: usize) -> Value {
+        let mut object = identifier(&go.kind, &go.id);
+        if let Some(attributes) = go.attributes.as_ref().and_then(|a| storage_to_map(a).ok()) {
+            object.extend(attributes);
+        }
+
+        self.path.push(Identifier::from(go));
+        for (name, r) in go.relationships.iter().flatten() {
+            let value = match &r.data {
+                OptionalVec::NotPresent => continue,
+                OptionalVec::One(None) => Value::Null,
+                OptionalVec::One(Some(id)) => match self.related(id, depth) {
+                    Some(value) => value,
+                    None => continue,
+                },
+                OptionalVec::Many(many) => many.iter().filter_map(|id| self.related(id, depth)).collect(),
+            };
+            object.insert(name.clone(), value);
+        }
+        self.path.pop();
+
+        Value::Object(object)
+    }
+
+    fn related(&mut self, id: &Identifier, depth: usize) -> Option<Value> {
+        let cycle = self.path.contains(id);
+        match self.index.get(id).copied() {
+            Some(_) if cycle && self.cycles == Cycles::Omit => None,
+            Some(go) if depth > 0 && (!cycle || self.cycles == Cycles::Repeat) => {
+                Some(self.resource(go, depth - 1))
+            },
+            _ => Some(Value::Object(identifier(&id.kind, &id.id))),
+        }
+    }
+}
+
+fn identifier(kind: &str, id: &str) -> JsonMap<String, Value> {
+    let mut object = JsonMap::new();
+    object.insert("type".into(), kind.into());
+    if !id.is_empty() {
+        object.insert("id".into(), id.into());
+    }
+    object
+}
+
+struct Normalizer<'a> {
+    schema: &'a Schema,
+    included: Vec<GenericObject>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Normalizer<'a> {
+    /// Converts a nested resource, moving the resources related to it into `included`
+    ///
+    /// `hint` is the type to assume when the resource has no `type` member
+    fn resource(&mut self, value: &Value, hint: Option<&str>, pointer: &str, primary: bool)
+        -> Option<GenericObject> {
+        let object = match value {
+            Value::Object(object) => object,
+            _ => {
+                self.errors.push(bad_request("Expected a resource object".into(), pointer));
+                return None;
+            },
+        };
+
+        let kind = match (object.get("type"), hint) {
+            (Some(Value::String(kind)), _) => kind.as_str(),
+            (None, Some(hint)) => hint,
+            (None, None) => {
+                self.errors.push(bad_request("Missing type for resource".into(), pointer));
+                return None;
+            },
+            (Some(_), _) => {
+                self.errors.push(bad_request("Expected a string".into(), &format!("{}/type", pointer)));
+                return None;
+            },
+        };
+        let id = match object.get("id") {
+            Some(Value::String(id)) => id.clone(),
+            Some(Value::Number(id)) => id.to_string(),
+            None if primary => String::new(),
+            None => {
+                self.errors.push(bad_request("Missing id for related resource".into(), pointer));
+                return None;
+            },
+            Some(_) => {
+                self.errors.push(bad_request("Expected a string".into(), &format!("{}/id", pointer)));
+                return None;
+            },
+        };
+        let t = match self.schema.types.get(kind) {
+            Some(t) => t,
+            None => {
+                self.errors.push(bad_request(format!("Unknown type {}", kind), pointer));
+                return None;
+            },
+        };
+
+        let mut attributes = Map::new();
+        let mut relationships = Relationships::new();
+        for (name, value) in object {
+            if name == "type" || name == "id" {
+                continue;
+            }
+            let schema = match t.relationships.get(name) {
+                Some(schema) => schema,
+                None => {
+                    attributes.insert(name.clone(), value.clone());
+                    continue;
+                },
+            };
+            let pointer = format!("{}/{}", pointer, escape(name));
+            let hint = match schema.targets.as_slice() {
+                [target] => Some(target.as_str()),
+                _ => None,
+            };
+            let data = match (value, schema.cardinality) {
+                (Value::Array(_), Cardinality::ToOne) => {
+                    self.errors.push(bad_request(
+                        format!("Expected a single resource for to-one relationship {}", name), &pointer));
+                    continue;
+                },
+                (Value::Array(many), Cardinality::ToMany) => OptionalVec::Many(many.iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| self.related(v, hint, &format!("{}/{}", pointer, i)))
+                    .collect()),
+                (_, Cardinality::ToMany) => {
+                    self.errors.push(bad_request(
+                        format!("Expected an array of resources for to-many relationship {}", name), &pointer));
+                    continue;
+                },
+                (Value::Null, Cardinality::ToOne) => OptionalVec::One(None),
+                (one, Cardinality::ToOne) => match self.related(one, hint, &pointer) {
+                    Some(id) => OptionalVec::One(Some(id)),
+                    None => continue,
+                },
+            };
+            relationships.insert(name.clone(), Relationship {
+                data,
+                ..Default::default()
+            });
+        }
+
+        Some(GenericObject {
+            id,
+            kind: kind.into(),
+            attributes: if attributes.is_empty() { None } else { Some(map_to_storage(attributes)) },
+            relationships: if relationships.is_empty() { None } else { Some(relationships) },
+            links: None,
+            meta: None,
+        })
+    }
+
+    /// Converts a related resource to its identifier, including it if it has more than a
+    /// `type` and `id`
+    fn related(&mut self, value: &Value, hint: Option<&str>, pointer: &str) -> Option<Identifier> {
+        let go = self.resource(value, hint, pointer, false)?;
+        let id = Identifier::new(go.id.clone(), go.kind.clone());
+        if go.attributes.is_some() || go.relationships.is_some() {
+            self.included.push(go);
+        }
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CreateDocument;
+    use crate::{RelationshipSchema, TypeSchema};
+    use serde_json::json;
+
+    fn document() -> Document {
+        serde_json::from_value(json!({
+            "data": {
+                "type": "articles",
+                "id": "1",
+                "attributes": { "title": "Rails is Omakase" },
+                "relationships": {
+                    "author": { "data": { "type": "people", "id": "9" } },
+                    "comments": { "data": [
+                        { "type": "comments", "id": "5" },
+                        { "type": "comments", "id": "12" }
+                    ] },
+                    "editor": { "links": { "related": "/articles/1/editor" } }
+                },
+                "links": { "self": "/articles/1" }
+            },
+            "included": [{
+                "type": "people",
+                "id": "9",
+                "attributes": { "first-name": "Dan" },
+                "relationships": {
+                    "articles": { "data": [{ "type": "articles", "id": "1" }] }
+                }
+            }, {
+                "type": "comments",
+                "id": "5",
+                "attributes": { "body": "First!" },
+                "relationships": {
+                    "author": { "data": null }
+                }
+            }]
+        })).unwrap()
+    }
+
+    fn schema() -> Schema {
+        let mut articles = TypeSchema::default();
+        articles.relationships.insert("author".into(), RelationshipSchema::to_one("people".into()));
+        articles.relationships.insert("comments".into(), RelationshipSchema::to_many("comments".into()));
+        let mut people = TypeSchema::default();
+        people.relationships.insert("articles".into(), RelationshipSchema::to_many("articles".into()));
+        let mut comments = TypeSchema::default();
+        comments.relationships.insert("author".into(), RelationshipSchema::to_one("people".into()));
+
+        let mut schema = Schema::new();
+        schema.types.insert("articles".into(), articles);
+        schema.types.insert("people".into(), people);
+        schema.types.insert("comments".into(), comments);
+        schema
+    }
+
+    #[test]
+    fn denormalize() {
+        let d = document();
+        assert_eq!(d.denormalize(0), json!({
+            "type": "articles",
+            "id": "1",
+            "title": "Rails is Omakase",
+            "author": { "type": "people", "id": "9" },
+            "comments": [{ "type": "comments", "id": "5" }, { "type": "comments", "id": "12" }],
+        }));
+
+        let value = d.denormalize(2);
+        assert_eq!(value["author"]["first-name"], "Dan");
+        assert_eq!(value["author"]["articles"], json!([{ "type": "articles", "id": "1" }]));
+        assert_eq!(value["comments"], json!([
+            { "type": "comments", "id": "5", "body": "First!", "author": null },
+            { "type": "comments", "id": "12" },
+        ]));
+
+        let d : Document = Document { data: OptionalVec::Many(Vec::new()), ..Default::default() };
+        assert_eq!(d.denormalize(1), json!([]));
+        assert_eq!(Document::default().denormalize(1), Value::Null);
+    }
+
+    #[test]
+    fn cycles() {
+        let d = document();
+        let value = d.denormalize_with(3, Cycles::Omit);
+        assert_eq!(value["author"]["articles"], json!([]));
+
+        let value = d.denormalize_with(3, Cycles::Repeat);
+        assert_eq!(value["author"]["articles"][0]["title"], "Rails is Omakase");
+        assert_eq!(value["author"]["articles"][0]["author"]["articles"], json!([{ "type": "articles", "id": "1" }]));
+    }
+
+    #[test]
+    fn normalize() {
+        let d = document();
+        let normalized = Document::normalize(&d.denormalize(2), "articles", &schema()).unwrap();
+        assert_eq!(normalized.identifiers(), d.identifiers());
+        assert_eq!(normalized.included.as_ref().unwrap().len(), 2);
+        assert_eq!(normalized.denormalize(2), d.denormalize(2));
+
+        let value = json!([{
+            "title": "No type",
+            "author": { "id": 9, "first-name": "Dan" },
+            "comments": [],
+        }]);
+        let normalized = Document::normalize(&value, "articles", &schema()).unwrap();
+        assert_eq!(serde_json::to_value(&normalized).unwrap(), json!({
+            "data": [{
+                "type": "articles",
+                "id": "",
+                "attributes": { "title": "No type" },
+                "relationships": {
+                    "author": { "data": { "type": "people", "id": "9" } },
+                    "comments": { "data": [] },
+                },
+            }],
+            "included": [{ "type": "people", "id": "9", "attributes": { "first-name": "Dan" } }],
+        }));
+        let create = CreateDocument { data: normalized.data, ..Default::default() };
+        assert!(serde_json::to_value(&create).unwrap()["data"][0].get("id").is_none());
+    }
+
+    #[test]
+    fn normalize_errors() {
+        let value = json!({
+            "author": [{ "id": "9" }],
+            "comments": [{ "body": "No id" }, 5],
+        });
+        let errors = Document::normalize(&value, "articles", &schema()).unwrap_err();
+        let mut pointers : Vec<_> = errors.iter()
+            .map(|e| e.source.as_ref().unwrap().pointer.clone().unwrap())
+            .collect();
+        pointers.sort();
+        assert_eq!(pointers, vec!["/author", "/comments/0", "/comments/1"]);
+
+        let errors = Document::normalize(&json!({}), "unknown", &schema()).unwrap_err();
+        assert_eq!(errors[0].detail, Some("Unknown type unknown".into()));
+    }
+}
@@ -1,10 +1,27 @@
-use crate::{OptionalVec, Meta, GenericObject, JsonApi, Links, Error};
+use crate::{
+    OptionalVec,
+    Meta,
+    GenericObject,
+    Identifier,
+    IdentifierSet,
+    IdentifierMap,
+    JsonApi,
+    Links,
+    Relationships,
+    Error,
+    ToManyOperation,
+};
+use crate::object::AttributeStorage;
+use crate::validation::bad_request;
+use serde::{ser::{Serialize, Serializer}, de::{Deserialize, Deserializer, Error as _, IgnoredAny}};
 use serde_derive::{Serialize, Deserialize};
 
 /// The object at the root of every JSON:API message
 ///
 /// For validitity it must contain at least one of `data`, `errors`, or `meta`
 ///
+/// See `RelationshipDocument` for documents exchanged with relationship endpoints
+///
 /// See the [JSON:API docs](https://jsonapi.org/format/#document-top-level) for more information
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct Document {
@@ -41,10 +58,219 @@ impl Default for Document {
     }
 }
 
+impl Document {
+    /// Collects the identifiers of the primary data and included resources
+    pub fn identifiers(&self) -> IdentifierSet {
+        self.resources().map(Identifier::from).collect()
+    }
+
+    /// Indexes the primary data and included resources by their identifier
+    ///
+    /// When a resource appears more than once the first occurrence wins, primary data
+    /// before included resources
+    pub fn index(&self) -> IdentifierMap<&GenericObject> {
+        let mut index = IdentifierMap::new();
+        for go in self.resources() {
+            index.entry(Identifier::from(go)).or_insert(go);
+        }
+        index
+    }
+
+    /// Removes included resources that repeat the primary data or an earlier included
+    /// resource
+    ///
+    /// See the [JSON:API docs](https://jsonapi.org/format/#document-compound-documents)
+    /// for more information
+    pub fn dedup_included(&mut self) {
+        let mut seen : IdentifierSet = self.primary().map(Identifier::from).collect();
+        if let Some(included) = &mut self.included {
+            included.retain(|go| seen.insert(Identifier::from(go)));
+        }
+    }
+
+    fn primary(&self) -> impl Iterator<Item = &GenericObject> {
+        let data : &[GenericObject] = match &self.data {
+            OptionalVec::One(Some(go)) => std::slice::from_ref(go),
+            OptionalVec::Many(many) => many,
+            _ => &[],
+        };
+        data.iter()
+    }
+
+    fn resources(&self) -> impl Iterator<Item = &GenericObject> {
+        self.primary().chain(self.included.iter().flatten())
+    }
+}
+
+/// A request document sent to create a resource
+///
+/// Unlike in a `Document`, the primary data may leave out its `id` so the server can generate
+/// one, in which case the `GenericObject` has an empty id
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#crud-creating) for more information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct CreateDocument {
+    /// The document's primary data
+    #[serde(skip_serializing_if = "OptionalVec::is_not_present", default,
+        serialize_with = "serialize_new_resources", deserialize_with = "deserialize_new_resources")]
+    pub data: OptionalVec<GenericObject>,
+    /// Non-standard meta information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+    /// Information about the version of JSON:API being used 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jsonapi: Option<JsonApi>,
+}
+
+/// A resource object whose `id` may be left out
+#[derive(Serialize, Deserialize)]
+struct NewObject {
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<AttributeStorage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    relationships: Option<Relationships>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    links: Option<Links>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<Meta>,
+}
+
+impl From<NewObject> for GenericObject {
+    fn from(o: NewObject) -> Self {
+        Self {
+            id: o.id,
+            kind: o.kind,
+            attributes: o.attributes,
+            relationships: o.relationships,
+            links: o.links,
+            meta: o.meta,
+        }
+    }
+}
+
+impl From<GenericObject> for NewObject {
+    fn from(go: GenericObject) -> Self {
+        Self {
+            id: go.id,
+            kind: go.kind,
+            attributes: go.attributes,
+            relationships: go.relationships,
+            links: go.links,
+            meta: go.meta,
+        }
+    }
+}
+
+fn serialize_new_resources<S>(data: &OptionalVec<GenericObject>, serializer: S) -> Result<S::Ok, S::Error>
+where S: Serializer {
+    let data : OptionalVec<NewObject> = match data {
+        OptionalVec::NotPresent => OptionalVec::NotPresent,
+        OptionalVec::One(one) => OptionalVec::One(one.clone().map(NewObject::from)),
+        OptionalVec::Many(many) => OptionalVec::Many(many.iter().cloned().map(NewObject::from).collect()),
+    };
+    data.serialize(serializer)
+}
+
+fn deserialize_new_resources<'de, D>(deserializer: D) -> Result<OptionalVec<GenericObject>, D::Error>
+where D: Deserializer<'de> {
+    Ok(match OptionalVec::<NewObject>::deserialize(deserializer)? {
+        OptionalVec::NotPresent => OptionalVec::NotPresent,
+        OptionalVec::One(one) => OptionalVec::One(one.map(GenericObject::from)),
+        OptionalVec::Many(many) => OptionalVec::Many(many.into_iter().map(GenericObject::from).collect()),
+    })
+}
+
+/// A document whose primary data is resource identifier objects, as exchanged with
+/// relationship endpoints
+///
+/// Unlike identifiers found elsewhere, the primary data may not have the `attributes`,
+/// `relationships` or `links` of a resource object, so resource objects sent to a relationship
+/// endpoint are rejected
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#fetching-relationships) for more
+/// information
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct RelationshipDocument {
+    /// The document's primary data
+    #[serde(skip_serializing_if = "OptionalVec::is_not_present", default,
+        deserialize_with = "strict_identifiers")]
+    pub data: OptionalVec<Identifier>,
+    /// Any errors that were encountered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<Error>>,
+    /// Non-standard meta information
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+    /// Information about the version of JSON:API being used 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jsonapi: Option<JsonApi>,
+    /// Links related to the primary data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Links>,
+    /// Included resources related to the primary data or each other
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub included: Option<Vec<GenericObject>>,
+}
+
+/// A resource identifier object along with the members only a resource object may have
+#[derive(Deserialize)]
+struct StrictIdentifier {
+    #[serde(flatten)]
+    identifier: Identifier,
+    attributes: Option<IgnoredAny>,
+    relationships: Option<IgnoredAny>,
+    links: Option<IgnoredAny>,
+}
+
+fn strict_identifiers<'de, D>(deserializer: D) -> Result<OptionalVec<Identifier>, D::Error>
+where D: Deserializer<'de> {
+    let strict = |id: StrictIdentifier| {
+        if id.attributes.is_some() || id.relationships.is_some() || id.links.is_some() {
+            Err(D::Error::custom("expected a resource identifier, got a resource object"))
+        } else {
+            Ok(id.identifier)
+        }
+    };
+    Ok(match OptionalVec::<StrictIdentifier>::deserialize(deserializer)? {
+        OptionalVec::NotPresent => OptionalVec::NotPresent,
+        OptionalVec::One(one) => OptionalVec::One(one.map(strict).transpose()?),
+        OptionalVec::Many(many) => OptionalVec::Many(many.into_iter().map(strict).collect::<Result<_, _>>()?),
+    })
+}
+
+impl RelationshipDocument {
+    /// Converts the body of a `POST` to a to-many relationship endpoint
+    pub fn into_add(self) -> Result<ToManyOperation, Vec<Error>> {
+        self.into_many().map(ToManyOperation::Add)
+    }
+
+    /// Converts the body of a `DELETE` to a to-many relationship endpoint
+    pub fn into_remove(self) -> Result<ToManyOperation, Vec<Error>> {
+        self.into_many().map(ToManyOperation::Remove)
+    }
+
+    /// Converts the body of a `PATCH` to a to-many relationship endpoint
+    pub fn into_replace(self) -> Result<ToManyOperation, Vec<Error>> {
+        self.into_many().map(ToManyOperation::Replace)
+    }
+
+    fn into_many(self) -> Result<Vec<Identifier>, Vec<Error>> {
+        match self.data {
+            OptionalVec::Many(many) => Ok(many),
+            _ => Err(vec![bad_request(
+                "Expected an array of resource identifiers for a to-many relationship".into(),
+                "/data")]),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde_empty() {
@@ -70,6 +296,110 @@ mod test {
         let d2 = serde_json::from_str(&s).unwrap();
         assert_eq!(d1, d2);
     }
+
+    #[test]
+    fn serde_relationship() {
+        let d1 = RelationshipDocument {
+            data: OptionalVec::Many(vec![Identifier::new("a".into(), "b".into())]),
+            ..Default::default()
+        };
+        let s = serde_json::to_string(&d1).unwrap();
+        assert_eq!(s, "{\"data\":[{\"id\":\"a\",\"type\":\"b\"}]}");
+        let d2 = serde_json::from_str(&s).unwrap();
+        assert_eq!(d1, d2);
+
+        let s = "{\"data\":{\"id\":\"a\",\"type\":\"b\",\"attributes\":{}}}";
+        assert!(serde_json::from_str::<RelationshipDocument>(s).is_err());
+    }
+
+    fn object(id: &str, kind: &str, title: &str) -> GenericObject {
+        let mut meta = Meta::new();
+        meta.insert("title".into(), serde_json::json!(title));
+        GenericObject {
+            id: id.into(),
+            kind: kind.into(),
+            attributes: None,
+            relationships: None,
+            links: None,
+            meta: Some(meta),
+        }
+    }
+
+    #[test]
+    fn index() {
+        let mut d : Document = Document {
+            data: OptionalVec::Many(vec![object("1", "a", "first")]),
+            included: Some(vec![
+                object("1", "b", "included"),
+                object("1", "a", "repeat"),
+                object("1", "b", "repeat"),
+            ]),
+            ..Default::default()
+        };
+        let a = Identifier::new("1".into(), "a".into());
+        let b = Identifier::new("1".into(), "b".into());
+
+        assert_eq!(d.identifiers().into_iter().collect::<Vec<_>>(), vec![a.clone(), b.clone()]);
+        let index = d.index();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[&a].meta.as_ref().unwrap()["title"], "first");
+        assert_eq!(index[&b].meta.as_ref().unwrap()["title"], "included");
+
+        d.dedup_included();
+        assert_eq!(d.included, Some(vec![object("1", "b", "included")]));
+    }
+
+    #[test]
+    fn linkage_extra_members() {
+        let s = r#"{"data":{"type":"a","id":"1","relationships":{"b":{"data":{"type":"b","id":"2","lid":"x"}}}}}"#;
+        assert!(serde_json::from_str::<Document>(s).is_ok());
+
+        let s = r#"{"data":[{"type":"b","id":"2","lid":"x","meta":{}}]}"#;
+        let d : RelationshipDocument = serde_json::from_str(s).unwrap();
+        assert_eq!(d.data, OptionalVec::Many(vec![Identifier {
+            id: "2".into(),
+            kind: "b".into(),
+            meta: Some(Meta::new()),
+        }]));
+        let s = r#"{"data":[{"type":"b","id":"2","relationships":{}}]}"#;
+        assert!(serde_json::from_str::<RelationshipDocument>(s).is_err());
+        let s = r#"{"data":{"type":"b","id":"2","links":{}}}"#;
+        assert!(serde_json::from_str::<RelationshipDocument>(s).is_err());
+    }
+
+    #[test]
+    fn into_operations() {
+        let id = Identifier::new("a".into(), "b".into());
+        let d = RelationshipDocument {
+            data: OptionalVec::Many(vec![id.clone()]),
+            ..Default::default()
+        };
+        assert_eq!(d.clone().into_add().unwrap(), ToManyOperation::Add(vec![id.clone()]));
+        assert_eq!(d.clone().into_remove().unwrap(), ToManyOperation::Remove(vec![id.clone()]));
+        assert_eq!(d.into_replace().unwrap(), ToManyOperation::Replace(vec![id.clone()]));
+
+        let d = RelationshipDocument {
+            data: OptionalVec::One(Some(id)),
+            ..Default::default()
+        };
+        assert_eq!(d.into_add().unwrap_err()[0].status, Some("400".into()));
+    }
+
+    #[test]
+    fn serde_create() {
+        let s = "{\"data\":{\"type\":\"b\"}}";
+        assert!(serde_json::from_str::<Document>(s).is_err());
+        let d : CreateDocument = serde_json::from_str(s).unwrap();
+        match &d.data {
+            OptionalVec::One(Some(go)) => assert_eq!(go.id, ""),
+            _ => panic!("expected a single resource"),
+        }
+        assert_eq!(serde_json::to_string(&d).unwrap(), s);
+
+        let s = "{\"data\":{\"id\":\"a\",\"type\":\"b\"}}";
+        let d : CreateDocument = serde_json::from_str(s).unwrap();
+        assert_eq!(serde_json::to_string(&d).unwrap(), s);
+    }
 }
 
 
@@ -4,7 +4,7 @@ use serde_derive::{Serialize, Deserialize};
 /// Additional information about any errors encountered while processing a request 
 ///
 /// See the [JSON:API docs](https://jsonapi.org/format/#errors) for more information
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
 pub struct Error {
     /// A unique identifier for this particular occurrence of the problem
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -33,21 +33,6 @@ pub struct Error {
     pub meta: Option<Meta>,
 }
 
-impl Default for Error {
-    fn default() -> Self {
-        Self {
-            id: None,
-            links: None,
-            status: None,
-            code: None,
-            title: None,
-            detail: None,
-            source: None,
-            meta: None,
-        }
-    }
-}
-
 /// Will fill the `detail` field with the `Display` of the error
 impl<E> From<E> for Error 
 where E : std::error::Error {
@@ -68,7 +53,6 @@ where E : std::error::Error {
 #[cfg(test)]
 mod error_test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde_empty() {
@@ -101,7 +85,7 @@ mod error_test {
 /// Information about the source of the error
 ///
 /// See the [JSON:API docs](https://jsonapi.org/format/#errors) for more information
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
 pub struct ErrorSource {
     /// A JSON pointer ([RFC 6901](https://tools.ietf.org/html/rfc6901)) to the associated
     /// entity in the request document
@@ -112,19 +96,9 @@ pub struct ErrorSource {
     pub parameter: Option<String>,
 }
 
-impl Default for ErrorSource {
-    fn default() -> Self {
-        Self {
-            pointer: None,
-            parameter: None,
-        }
-    }
-}
-
 #[cfg(test)]
 mod error_source_test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde_empty() {
@@ -0,0 +1,347 @@
+use crate::{Attributes, Cardinality, RelationshipSchema};
+use schemars::{
+    JsonSchema,
+    Schema,
+    SchemaGenerator,
+    generate::SchemaSettings,
+    json_schema,
+};
+use serde_json::{Map as JsonMap, Value, json};
+
+/// The name of the schema shared by every included resource object
+const RESOURCE : &str = "JsonApiResource";
+/// The name of the schema shared by every relationship object
+const RELATIONSHIP : &str = "JsonApiRelationship";
+/// The name of the schema shared by every resource identifier object
+const IDENTIFIER : &str = "JsonApiIdentifier";
+/// The name of the schema shared by every links object
+pub (crate) const LINKS : &str = "JsonApiLinks";
+
+/// Generates a JSON Schema (draft 2020-12) for a document whose primary data is a single
+/// resource of type `A`, or `null`
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-top-level) for more information
+pub fn resource_document_schema<A: Attributes + JsonSchema>() -> Schema {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let resource = resource_schema::<A>(&mut generator, false);
+    let mut schema = document_schema(&generator, json_schema!({
+        "oneOf": [resource, {"type": "null"}],
+    }));
+    schema.insert("title".into(), format!("{} document", A::kind()).into());
+    finish(&mut generator, &mut schema);
+    schema
+}
+
+/// Generates a JSON Schema (draft 2020-12) for a document whose primary data is an array of
+/// resources of type `A`
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-top-level) for more information
+pub fn collection_document_schema<A: Attributes + JsonSchema>() -> Schema {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let resource = resource_schema::<A>(&mut generator, false);
+    let mut schema = document_schema(&generator, json_schema!({
+        "type": "array",
+        "items": resource,
+    }));
+    schema.insert("title".into(), format!("{} collection document", A::kind()).into());
+    finish(&mut generator, &mut schema);
+    schema
+}
+
+/// Generates a JSON Schema (draft 2020-12) for a request document creating a resource of
+/// type `A`
+///
+/// An `id` is only allowed if `A::client_ids` allows it
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#crud-creating) for more information
+pub fn create_document_schema<A: Attributes + JsonSchema>() -> Schema {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let mut schema = create_schema(resource_schema::<A>(&mut generator, true));
+    schema.insert("title".into(), format!("{} create document", A::kind()).into());
+    finish(&mut generator, &mut schema);
+    schema
+}
+
+/// The schema of a document sent by a server with the primary data described by `data`
+pub (crate) fn document_schema(generator: &SchemaGenerator, data: Schema) -> Schema {
+    json_schema!({
+        "type": "object",
+        "required": ["data"],
+        "properties": {
+            "data": data,
+            "meta": {"type": "object"},
+            "jsonapi": {"type": "object"},
+            "links": reference(generator, LINKS),
+            "included": {
+                "type": "array",
+                "items": reference(generator, RESOURCE),
+            },
+        },
+    })
+}
+
+/// The schema of a request document creating the resource described by `resource`
+pub (crate) fn create_schema(resource: Schema) -> Schema {
+    json_schema!({
+        "type": "object",
+        "required": ["data"],
+        "properties": {
+            "data": resource,
+            "meta": {"type": "object"},
+        },
+    })
+}
+
+/// The schema of a resource object of type `A`, as sent by a client creating one when
+/// `create` is set and as sent by a server otherwise
+pub (crate) fn resource_schema<A>(generator: &mut SchemaGenerator, create: bool) -> Schema
+where A: Attributes + JsonSchema {
+    let mut properties = JsonMap::new();
+    properties.insert("type".into(), json!({"const": A::kind()}));
+    if !create || A::client_ids() {
+        properties.insert("id".into(), json!({"type": "string"}));
+    }
+    properties.insert("attributes".into(), generator.subschema_for::<A>().to_value());
+    let relationships = A::relationships();
+    properties.insert("relationships".into(), if relationships.is_empty() {
+        json!({
+            "type": "object",
+            "additionalProperties": reference(generator, RELATIONSHIP),
+        })
+    } else {
+        let names : JsonMap<String, Value> = relationships.into_iter()
+            .map(|(name, schema)| (name, relationship_schema(generator, &schema).to_value()))
+            .collect();
+        json!({
+            "type": "object",
+            "properties": names,
+            "additionalProperties": false,
+        })
+    });
+    if !create {
+        properties.insert("links".into(), reference(generator, LINKS).to_value());
+    }
+    properties.insert("meta".into(), json!({"type": "object"}));
+
+    json_schema!({
+        "type": "object",
+        "required": if create { vec!["type"] } else { vec!["type", "id"] },
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+/// The schema of a relationship described by `schema`, with linkage pointing to the
+/// identifiers of its targets
+fn relationship_schema(generator: &mut SchemaGenerator, schema: &RelationshipSchema) -> Schema {
+    if schema.targets.is_empty() {
+        return reference(generator, RELATIONSHIP);
+    }
+    let mut targets : Vec<Schema> = schema.targets.iter()
+        .map(|kind| identifier_schema(generator, kind))
+        .collect();
+    let identifier = if targets.len() == 1 {
+        targets.remove(0)
+    } else {
+        json_schema!({"oneOf": targets})
+    };
+    let data = match schema.cardinality {
+        Cardinality::ToOne => json!({"oneOf": [{"type": "null"}, identifier]}),
+        Cardinality::ToMany => json!({"type": "array", "items": identifier}),
+    };
+    json_schema!({
+        "type": "object",
+        "properties": {
+            "data": data,
+            "links": reference(generator, LINKS),
+            "meta": {"type": "object"},
+        },
+    })
+}
+
+/// Adds a `{type}.Identifier` schema for identifiers of resources of type `kind` to the
+/// generator's definitions and refers to it
+fn identifier_schema(generator: &mut SchemaGenerator, kind: &str) -> Schema {
+    let name = format!("{}.Identifier", kind);
+    generator.definitions_mut().entry(name.clone()).or_insert_with(|| json!({
+        "type": "object",
+        "required": ["type", "id"],
+        "properties": {
+            "type": {"const": kind},
+            "id": {"type": "string"},
+            "meta": {"type": "object"},
+        },
+        "additionalProperties": false,
+    }));
+    reference(generator, &name)
+}
+
+/// Adds the shared definitions to the generator and moves them all into `schema`
+fn finish(generator: &mut SchemaGenerator, schema: &mut Schema) {
+    add_definitions(generator);
+    let definitions_path = generator.settings().definitions_path.clone();
+    let definitions = generator.take_definitions(true);
+    if let Some(meta_schema) = &generator.settings().meta_schema {
+        schema.insert("$schema".into(), meta_schema.to_string().into());
+    }
+    schema.insert(definitions_path.trim_start_matches('/').into(), definitions.into());
+}
+
+/// Adds the schemas shared by every document to the generator's definitions
+pub (crate) fn add_definitions(generator: &mut SchemaGenerator) {
+    let identifier = json!({
+        "type": "object",
+        "required": ["type", "id"],
+        "properties": {
+            "type": {"type": "string"},
+            "id": {"type": "string"},
+            "meta": {"type": "object"},
+        },
+        "additionalProperties": false,
+    });
+    let relationship = json!({
+        "type": "object",
+        "properties": {
+            "data": {
+                "oneOf": [
+                    {"type": "null"},
+                    reference(generator, IDENTIFIER),
+                    {"type": "array", "items": reference(generator, IDENTIFIER)},
+                ],
+            },
+            "links": reference(generator, LINKS),
+            "meta": {"type": "object"},
+        },
+    });
+    let links = json!({
+        "type": "object",
+        "additionalProperties": {
+            "oneOf": [
+                {"type": "null"},
+                {"type": "string"},
+                {
+                    "type": "object",
+                    "properties": {
+                        "href": {"type": "string"},
+                        "meta": {"type": "object"},
+                    },
+                },
+            ],
+        },
+    });
+    let resource = json!({
+        "type": "object",
+        "required": ["type", "id"],
+        "properties": {
+            "type": {"type": "string"},
+            "id": {"type": "string"},
+            "attributes": {"type": "object"},
+            "relationships": {
+                "type": "object",
+                "additionalProperties": reference(generator, RELATIONSHIP),
+            },
+            "links": reference(generator, LINKS),
+            "meta": {"type": "object"},
+        },
+        "additionalProperties": false,
+    });
+
+    let definitions = generator.definitions_mut();
+    definitions.insert(IDENTIFIER.into(), identifier);
+    definitions.insert(RELATIONSHIP.into(), relationship);
+    definitions.insert(LINKS.into(), links);
+    definitions.insert(RESOURCE.into(), resource);
+}
+
+pub (crate) fn reference(generator: &SchemaGenerator, name: &str) -> Schema {
+    Schema::new_ref(format!("#{}/{}", generator.settings().definitions_path, name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_derive::{Serialize, Deserialize};
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "kebab-case")]
+    struct Article {
+        title: String,
+        word_count: Option<u32>,
+    }
+
+    impl Attributes for Article {
+        type Id = String;
+        fn kind() -> String { "articles".into() }
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct Tag {
+        name: String,
+    }
+
+    impl Attributes for Tag {
+        type Id = String;
+        fn kind() -> String { "tags".into() }
+        fn client_ids() -> bool { true }
+        fn relationships() -> Vec<(String, RelationshipSchema)> {
+            vec![
+                ("parent".into(), RelationshipSchema::to_one("tags".into())),
+                ("related".into(), RelationshipSchema {
+                    targets: Vec::new(),
+                    cardinality: Cardinality::ToMany,
+                }),
+            ]
+        }
+    }
+
+    fn data(schema: &Schema) -> &Value {
+        schema.pointer("/properties/data").unwrap()
+    }
+
+    #[test]
+    fn resource_document() {
+        let schema = resource_document_schema::<Article>();
+        assert_eq!(schema.get("$schema"), Some(&json!("https://json-schema.org/draft/2020-12/schema")));
+        assert_eq!(schema.get("title"), Some(&json!("articles document")));
+
+        let resource = &data(&schema)["oneOf"][0];
+        assert_eq!(resource["properties"]["type"], json!({"const": "articles"}));
+        assert_eq!(resource["required"], json!(["type", "id"]));
+        assert_eq!(resource["properties"]["attributes"], json!({"$ref": "#/$defs/Article"}));
+
+        let article = schema.pointer("/$defs/Article").unwrap();
+        assert_eq!(article["required"], json!(["title"]));
+        assert!(article["properties"]["word-count"].is_object());
+        for name in &[RESOURCE, RELATIONSHIP, IDENTIFIER, LINKS] {
+            assert!(schema.pointer(&format!("/$defs/{}", name)).is_some());
+        }
+        assert_eq!(schema.pointer("/properties/included/items"),
+            Some(&json!({"$ref": "#/$defs/JsonApiResource"})));
+    }
+
+    #[test]
+    fn collection_document() {
+        let schema = collection_document_schema::<Article>();
+        assert_eq!(data(&schema)["type"], json!("array"));
+        assert_eq!(data(&schema)["items"]["properties"]["type"], json!({"const": "articles"}));
+    }
+
+    #[test]
+    fn create_document() {
+        let schema = create_document_schema::<Article>();
+        assert_eq!(data(&schema)["required"], json!(["type"]));
+        assert!(data(&schema)["properties"].get("id").is_none());
+        assert!(data(&schema)["properties"].get("links").is_none());
+        assert!(schema.pointer("/properties/included").is_none());
+
+        let schema = create_document_schema::<Tag>();
+        assert!(data(&schema)["properties"].get("id").is_some());
+        let relationships = &data(&schema)["properties"]["relationships"]["properties"];
+        assert_eq!(relationships["parent"]["properties"]["data"]["oneOf"],
+            json!([{"type": "null"}, {"$ref": "#/$defs/tags.Identifier"}]));
+        assert_eq!(relationships["related"], json!({"$ref": "#/$defs/JsonApiRelationship"}));
+        assert_eq!(schema.pointer("/$defs/tags.Identifier/properties/type"), Some(&json!({"const": "tags"})));
+        assert_eq!(data(&schema)["properties"]["relationships"]["additionalProperties"], json!(false));
+        assert!(schema.pointer("/$defs/Tag").is_some());
+    }
+}
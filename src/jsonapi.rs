@@ -4,7 +4,7 @@ use serde_derive::{Serialize, Deserialize};
 /// Information about the implementation of JSON:API used by the server
 ///
 /// For more information see the [JSON:API docs](https://jsonapi.org/format/#document-jsonapi-object)
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
 pub struct JsonApi {
     /// The highest JSON:API version supported
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -14,19 +14,9 @@ pub struct JsonApi {
     pub meta: Option<Meta>,
 }
 
-impl Default for JsonApi {
-    fn default() -> Self {
-        Self {
-            version: None,
-            meta: None,
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde_empty() {
@@ -1,14 +1,86 @@
+mod map;
+pub use crate::map::Map;
+
 mod document;
-pub use crate::document::Document;
+pub use crate::document::{Document, CreateDocument, RelationshipDocument};
+
+mod borrowed;
+pub use crate::borrowed::{
+    BorrowedDocument,
+    BorrowedObject,
+    BorrowedRelationship,
+    BorrowedIdentifier,
+    BorrowedLinks,
+    BorrowedLink,
+};
+
+mod stream;
+pub use crate::stream::{DocumentReader, TypedDocumentReader, DocumentSection, write_document};
+#[cfg(feature = "async")]
+pub use crate::stream::write_document_async;
+
+mod polymorphic;
+pub use crate::polymorphic::ResourceEnum;
+#[doc(hidden)]
+pub use crate::polymorphic::unknown_type;
+
+mod registry;
+pub use crate::registry::{Registry, TypeDescription};
+
+mod schema;
+pub use crate::schema::{Schema, TypeSchema, AttributeSchema, AttributeType, RelationshipSchema, Cardinality};
+
+mod denormalize;
+pub use crate::denormalize::Cycles;
+
+mod model;
+pub use crate::model::{Model, ModelRelationships};
+
+mod store;
+pub use crate::store::Store;
+
+#[cfg(feature = "json-schema")]
+mod json_schema;
+#[cfg(feature = "json-schema")]
+pub use crate::json_schema::{resource_document_schema, collection_document_schema, create_document_schema};
+
+#[cfg(feature = "openapi")]
+mod openapi;
+#[cfg(feature = "openapi")]
+pub use crate::openapi::OpenApi;
+
+#[cfg(feature = "typescript")]
+mod typescript;
+#[cfg(feature = "typescript")]
+pub use crate::typescript::TypeScript;
+
+#[cfg(feature = "codegen")]
+mod codegen;
+#[cfg(feature = "codegen")]
+pub use crate::codegen::Codegen;
 
 mod error;
 pub use crate::error::{Error, ErrorSource};
 
+mod pointer;
+pub use crate::pointer::{resolve_pointer, PointerTarget, ResourceLocation};
+
+mod validation;
+pub use crate::validation::{validate_create, validate_update, validate_relationship_update};
+
 mod optional_vec;
 pub use crate::optional_vec::OptionalVec;
 
 mod object;
-pub use crate::object::{GenericObject, ResourceObject, Identifier, Attributes, ObjectConversionError};
+pub use crate::object::{
+    GenericObject,
+    ResourceObject,
+    Identifier,
+    IdentifierSet,
+    IdentifierMap,
+    Attributes,
+    ObjectConversionError,
+};
 
 mod meta;
 pub use crate::meta::Meta;
@@ -17,7 +89,7 @@ mod links;
 pub use crate::links::{Links, Link};
 
 mod relationships;
-pub use crate::relationships::{Relationship, Relationships};
+pub use crate::relationships::{Relationship, Relationships, ToManyOperation};
 
 mod jsonapi;
 pub use crate::jsonapi::JsonApi;
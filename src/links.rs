@@ -1,12 +1,10 @@
-use crate::Meta;
+use crate::{Meta, Map};
 use serde_derive::{Serialize, Deserialize};
-use serde_json::Value;
-use std::collections::BTreeMap;
 
 /// A collection of links
 ///
 /// See the [JSON:API docs](https://jsonapi.org/format/#document-links) for more information
-pub type Links = BTreeMap<String, Link>;
+pub type Links = Map<String, Link>;
 
 /// A link object
 /// 
@@ -29,10 +27,10 @@ pub enum Link {
 
 impl Link {
     /// Grabs the url of the link regardless of its variant
-    pub fn href<'a>(&'a self) -> Option<&'a str> {
+    pub fn href(&self) -> Option<&str> {
         match &self {
             Link::Url(href) => Some(href),
-            Link::Object{ href: Some(href), meta: _ } => Some(&href),
+            Link::Object{ href: Some(href), meta: _ } => Some(href),
             _ => None,
         }
     }
@@ -60,7 +58,6 @@ impl Default for Link {
 #[cfg(test)]
 mod link_test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde_url() {
@@ -75,7 +72,7 @@ mod link_test {
     fn serde_object() {
         let l1 = Link::Object{
             href: Some("a".into()),
-            meta: Some(BTreeMap::new()),
+            meta: Some(Map::new()),
         };
         let s = serde_json::to_string(&l1).unwrap();
         assert_eq!(s, "{\"href\":\"a\",\"meta\":{}}");
@@ -87,7 +84,6 @@ mod link_test {
 #[cfg(test)]
 mod links_test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde() {
@@ -98,7 +94,7 @@ mod links_test {
 
         let s = serde_json::to_string(&links).unwrap();
         assert_eq!(s, "{\"a\":\"b\",\"b\":{\"href\":\"c\"},\"c\":{}}");
-        let links2 = serde_json::from_str(&s).unwrap();
+        let links2 : Links = serde_json::from_str(&s).unwrap();
         assert_eq!(links, links2);
     }
 }
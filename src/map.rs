@@ -0,0 +1,13 @@
+/// The map behind `Meta`, `Links`, `Relationships` and resource attributes
+///
+/// Sorted by key by default, with the `preserve-order` feature enabled it is an `IndexMap`
+/// instead so objects are serialized in the order their members were inserted or parsed
+#[cfg(not(feature = "preserve-order"))]
+pub type Map<K, V> = std::collections::BTreeMap<K, V>;
+
+/// The map behind `Meta`, `Links`, `Relationships` and resource attributes
+///
+/// Sorted by key by default, with the `preserve-order` feature enabled it is an `IndexMap`
+/// instead so objects are serialized in the order their members were inserted or parsed
+#[cfg(feature = "preserve-order")]
+pub type Map<K, V> = indexmap::IndexMap<K, V>;
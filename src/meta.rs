@@ -1,10 +1,10 @@
 use serde_json::Value;
-use std::collections::BTreeMap;
+use crate::Map;
 
 /// An object containing non-standard meta information
 ///
 /// See the [JSON:API docs](https://jsonapi.org/format/#document-meta) for more information
-pub type Meta = BTreeMap<String, Value>;
+pub type Meta = Map<String, Value>;
 
 #[cfg(test)]
 mod test {
@@ -18,7 +18,16 @@ mod test {
         meta.insert("b".into(), Value::Null);
         let s = serde_json::to_string(&meta).unwrap();
         assert_eq!(s, "{\"a\":\"a\",\"b\":null}");
-        let meta2 = serde_json::from_str(&s).unwrap();
+        let meta2 : Meta = serde_json::from_str(&s).unwrap();
         assert_eq!(meta, meta2);
     }
+
+    #[cfg(feature = "preserve-order")]
+    #[test]
+    fn preserve_order() {
+        let s = "{\"b\":1,\"a\":{\"d\":2,\"c\":3}}";
+        let meta : Meta = serde_json::from_str(s).unwrap();
+        assert_eq!(meta.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+        assert_eq!(serde_json::to_string(&meta).unwrap(), s);
+    }
 }
@@ -0,0 +1,572 @@
+use crate::{
+    Cardinality,
+    Document,
+    GenericObject,
+    Identifier,
+    IdentifierMap,
+    Map,
+    ObjectConversionError,
+    OptionalVec,
+    Relationship,
+    Relationships,
+};
+use crate::object::{map_to_storage, storage_to_map};
+use serde::{ser::{Error as _, Serialize}, de::DeserializeOwned};
+use serde_json::{Error as SerdeError, Map as JsonMap, Value};
+use std::any::TypeId;
+
+/// A nested domain model loaded from a resource and the related resources of a compound
+/// document
+///
+/// The model's serialized form holds the resource's `id` and attributes, along with a member
+/// for each relationship declared in `relationships` holding the related model, `null`, or an
+/// array of them. Serde attributes decide how members are named, whether they may be left
+/// out, and so on
+///
+/// ```
+/// # use json_api::{Model, ModelRelationships};
+/// # use serde_derive::{Serialize, Deserialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct PersonView {
+///     id: String,
+///     #[serde(rename = "first-name")]
+///     first_name: String,
+/// }
+/// impl Model for PersonView {
+///     fn kind() -> String { "people".into() }
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct ArticleView {
+///     id: String,
+///     title: String,
+///     author: Option<PersonView>,
+///     comments: Vec<CommentView>,
+/// }
+/// impl Model for ArticleView {
+///     fn kind() -> String { "articles".into() }
+///     fn relationships(relationships: &mut ModelRelationships) {
+///         relationships
+///             .to_one::<PersonView>("author")
+///             .to_many::<CommentView>("comments");
+///     }
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct CommentView {
+///     id: String,
+///     body: String,
+/// }
+/// impl Model for CommentView {
+///     fn kind() -> String { "comments".into() }
+/// }
+/// ```
+pub trait Model: Serialize + DeserializeOwned + 'static {
+    /// The `type` of the resource the model is loaded from
+    fn kind() -> String;
+
+    /// Declares which members of the model are relationships, and the models of the related
+    /// resources
+    fn relationships(_relationships: &mut ModelRelationships) {}
+}
+
+/// The relationships declared by a `Model`
+#[derive(Default)]
+pub struct ModelRelationships {
+    fields: Vec<Field>,
+}
+
+impl ModelRelationships {
+    /// Declares the member `name` as a to-one relationship to `M`, held as `M` or `Option<M>`
+    pub fn to_one<M: Model>(&mut self, name: &str) -> &mut Self {
+        self.field::<M>(name, Cardinality::ToOne)
+    }
+
+    /// Declares the member `name` as a to-many relationship to `M`, held as `Vec<M>`
+    pub fn to_many<M: Model>(&mut self, name: &str) -> &mut Self {
+        self.field::<M>(name, Cardinality::ToMany)
+    }
+
+    fn field<M: Model>(&mut self, name: &str, cardinality: Cardinality) -> &mut Self {
+        self.fields.push(Field {
+            name: name.into(),
+            cardinality,
+            shape: Shape::of::<M>(),
+        });
+        self
+    }
+}
+
+struct Field {
+    name: String,
+    cardinality: Cardinality,
+    shape: Shape,
+}
+
+/// A `Model` with its type erased, so recursive models can be walked
+#[derive(Clone, Copy)]
+struct Shape {
+    model: TypeId,
+    kind: fn() -> String,
+    relationships: fn(&mut ModelRelationships),
+}
+
+impl Shape {
+    fn of<M: Model>() -> Self {
+        Self {
+            model: TypeId::of::<M>(),
+            kind: M::kind,
+            relationships: M::relationships,
+        }
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let mut relationships = ModelRelationships::default();
+        (self.relationships)(&mut relationships);
+        relationships.fields
+    }
+}
+
+impl Document {
+    /// Loads the primary data into `M`, resolving the model's relationships through the
+    /// primary data and `included`
+    ///
+    /// Relationships without data are left out of the model. Related resources must be of
+    /// the declared model's type and present in the document, and may not lead back to a
+    /// resource already being loaded into the same model further up
+    pub fn load<M: Model>(&self) -> Result<OptionalVec<M>, ObjectConversionError> {
+        let mut loader = Loader {
+            index: self.index(),
+            path: Vec::new(),
+        };
+        let shape = Shape::of::<M>();
+        let mut model = |go| -> Result<M, ObjectConversionError> {
+            Ok(serde_json::from_value(loader.resource(go, shape)?)?)
+        };
+        Ok(match &self.data {
+            OptionalVec::NotPresent => OptionalVec::NotPresent,
+            OptionalVec::One(None) => OptionalVec::One(None),
+            OptionalVec::One(Some(go)) => OptionalVec::One(Some(model(go)?)),
+            OptionalVec::Many(many) => OptionalVec::Many(many.iter().map(model).collect::<Result<_, _>>()?),
+        })
+    }
+
+    /// Splits models back into primary data and included resources
+    ///
+    /// Every related model becomes a resource identifier in its relationship and a resource in
+    /// `included`. A resource reached through several models, or again as the primary data, is
+    /// merged into a single copy holding the attributes and relationships of all of them, later
+    /// ones overwriting earlier ones. Only the primary data may be missing an `id`
+    pub fn dump<M: Model>(data: &OptionalVec<M>) -> Result<Document, ObjectConversionError> {
+        let mut dumper = Dumper {
+            included: Vec::new(),
+        };
+        let shape = Shape::of::<M>();
+        let mut resource = |m| -> Result<GenericObject, ObjectConversionError> {
+            dumper.resource(serde_json::to_value(m)?, shape, true)
+        };
+        let mut data = match data {
+            OptionalVec::NotPresent => OptionalVec::NotPresent,
+            OptionalVec::One(None) => OptionalVec::One(None),
+            OptionalVec::One(Some(m)) => OptionalVec::One(Some(resource(m)?)),
+            OptionalVec::Many(many) => OptionalVec::Many(many.iter().map(resource).collect::<Result<_, _>>()?),
+        };
+
+        let included = merge_duplicates(&mut data, dumper.included);
+        Ok(Document {
+            data,
+            included: if included.is_empty() { None } else { Some(included) },
+            ..Default::default()
+        })
+    }
+}
+
+/// Merges related resources into the primary data or an earlier copy of themselves, keeping
+/// the order they were first reached in
+fn merge_duplicates(data: &mut OptionalVec<GenericObject>, related: Vec<GenericObject>) -> Vec<GenericObject> {
+    let primary : &mut [GenericObject] = match data {
+        OptionalVec::One(Some(go)) => std::slice::from_mut(go),
+        OptionalVec::Many(many) => many,
+        _ => &mut [],
+    };
+    let primary_positions : IdentifierMap<usize> = primary.iter()
+        .enumerate()
+        .map(|(i, go)| (Identifier::from(go), i))
+        .collect();
+    let mut included : Vec<GenericObject> = Vec::new();
+    let mut positions : IdentifierMap<usize> = IdentifierMap::new();
+    for go in related {
+        let id = Identifier::from(&go);
+        if let Some(&i) = primary_positions.get(&id) {
+            primary[i].merge(go);
+        } else if let Some(&i) = positions.get(&id) {
+            included[i].merge(go);
+        } else {
+            positions.insert(id, included.len());
+            included.push(go);
+        }
+    }
+    included
+}
+
+struct Loader<'a> {
+    index: IdentifierMap<&'a GenericObject>,
+    /// The resources currently being loaded and their models, outermost first
+    path: Vec<(Identifier, TypeId)>,
+}
+
+impl<'a> Loader<'a> {
+    fn resource(&mut self, go: &GenericObject, shape: Shape) -> Result<Value, ObjectConversionError> {
+        let kind = (shape.kind)();
+        if go.kind != kind {
+            return Err(ObjectConversionError::ImproperType {
+                expected: kind,
+                got: go.kind.clone(),
+            });
+        }
+
+        let mut object = JsonMap::new();
+        if !go.id.is_empty() {
+            object.insert("id".into(), go.id.clone().into());
+        }
+        if let Some(attributes) = &go.attributes {
+            object.extend(storage_to_map(attributes)?);
+        }
+
+        self.path.push((Identifier::from(go), shape.model));
+        for field in shape.fields() {
+            let data = match go.relationships.as_ref().and_then(|r| r.get(&field.name)) {
+                Some(r) => &r.data,
+                None => continue,
+            };
+            let value = match data {
+                OptionalVec::NotPresent => continue,
+                OptionalVec::One(None) => Value::Null,
+                OptionalVec::One(Some(id)) => self.related(&field, id)?,
+                OptionalVec::Many(many) => many.iter()
+                    .map(|id| self.related(&field, id))
+                    .collect::<Result<_, _>>()?,
+            };
+            object.insert(field.name, value);
+        }
+        self.path.pop();
+
+        Ok(Value::Object(object))
+    }
+
+    fn related(&mut self, field: &Field, id: &Identifier) -> Result<Value, ObjectConversionError> {
+        match self.index.get(id).copied() {
+            Some(go) if !self.path.iter().any(|(i, m)| i == id && *m == field.shape.model) => {
+                self.resource(go, field.shape)
+            },
+            _ => Err(ObjectConversionError::UnresolvedRelationship {
+                relationship: field.name.clone(),
+                kind: id.kind.clone(),
+                id: id.id.clone(),
+            }),
+        }
+    }
+}
+
+struct Dumper {
+    included: Vec<GenericObject>,
+}
+
+impl Dumper {
+    fn resource(&mut self, value: Value, shape: Shape, primary: bool) -> Result<GenericObject, ObjectConversionError> {
+        let object = match value {
+            Value::Object(object) => object,
+            _ => return Err(SerdeError::custom("expected a model to serialize to a map").into()),
+        };
+
+        let fields = shape.fields();
+        let mut id = String::new();
+        let mut attributes = Map::new();
+        let mut relationships = Relationships::new();
+        for (name, value) in object {
+            if name == "id" {
+                id = match value {
+                    Value::String(id) => id,
+                    Value::Number(id) => id.to_string(),
+                    Value::Null => String::new(),
+                    other => return Err(ObjectConversionError::InvalidId{
+                        id: other.to_string(),
+                        reason: "expected a string or a number".into(),
+                    }),
+                };
+                continue;
+            }
+            let field = match fields.iter().find(|f| f.name == name) {
+                Some(field) => field,
+                None => {
+                    attributes.insert(name, value);
+                    continue;
+                },
+            };
+            let data = match (value, field.cardinality) {
+                (Value::Null, Cardinality::ToOne) => OptionalVec::One(None),
+                (Value::Null, Cardinality::ToMany) => continue,
+                (Value::Array(many), Cardinality::ToMany) => OptionalVec::Many(many.into_iter()
+                    .map(|v| self.related(v, field.shape))
+                    .collect::<Result<_, _>>()?),
+                (one @ Value::Object(_), Cardinality::ToOne) => OptionalVec::One(Some(self.related(one, field.shape)?)),
+                _ => return Err(SerdeError::custom(
+                    format!("relationship {} does not match its cardinality", name)).into()),
+            };
+            relationships.insert(name, Relationship {
+                data,
+                ..Default::default()
+            });
+        }
+        if id.is_empty() && !primary {
+            return Err(ObjectConversionError::InvalidId{
+                id,
+                reason: "only the primary data may be missing an id".into(),
+            });
+        }
+
+        Ok(GenericObject {
+            id,
+            kind: (shape.kind)(),
+            attributes: if attributes.is_empty() { None } else { Some(map_to_storage(attributes)) },
+            relationships: if relationships.is_empty() { None } else { Some(relationships) },
+            links: None,
+            meta: None,
+        })
+    }
+
+    fn related(&mut self, value: Value, shape: Shape) -> Result<Identifier, ObjectConversionError> {
+        let go = self.resource(value, shape, false)?;
+        let id = Identifier::new(go.id.clone(), go.kind.clone());
+        self.included.push(go);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_derive::{Serialize, Deserialize};
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct PersonView {
+        id: String,
+        #[serde(rename = "first-name")]
+        first_name: String,
+    }
+
+    impl Model for PersonView {
+        fn kind() -> String {
+            "people".into()
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct CommentView {
+        id: String,
+        body: String,
+        author: Option<PersonView>,
+    }
+
+    impl Model for CommentView {
+        fn kind() -> String {
+            "comments".into()
+        }
+
+        fn relationships(relationships: &mut ModelRelationships) {
+            relationships.to_one::<PersonView>("author");
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct ArticleView {
+        id: String,
+        title: String,
+        author: PersonView,
+        comments: Vec<CommentView>,
+    }
+
+    impl Model for ArticleView {
+        fn kind() -> String {
+            "articles".into()
+        }
+
+        fn relationships(relationships: &mut ModelRelationships) {
+            relationships
+                .to_one::<PersonView>("author")
+                .to_many::<CommentView>("comments");
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct PersonContact {
+        id: String,
+        email: String,
+    }
+
+    impl Model for PersonContact {
+        fn kind() -> String {
+            "people".into()
+        }
+    }
+
+    /// Reaches the same person through two different models
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Review {
+        id: String,
+        author: PersonView,
+        contact: PersonContact,
+    }
+
+    impl Model for Review {
+        fn kind() -> String {
+            "reviews".into()
+        }
+
+        fn relationships(relationships: &mut ModelRelationships) {
+            relationships
+                .to_one::<PersonView>("author")
+                .to_one::<PersonContact>("contact");
+        }
+    }
+
+    /// A model that refers back to itself
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Thread {
+        id: String,
+        #[serde(default)]
+        replies: Vec<Thread>,
+    }
+
+    impl Model for Thread {
+        fn kind() -> String {
+            "comments".into()
+        }
+
+        fn relationships(relationships: &mut ModelRelationships) {
+            relationships.to_many::<Thread>("replies");
+        }
+    }
+
+    fn document() -> Document {
+        serde_json::from_value(json!({
+            "data": {
+                "type": "articles",
+                "id": "1",
+                "attributes": { "title": "Rails is Omakase" },
+                "relationships": {
+                    "author": { "data": { "type": "people", "id": "9" } },
+                    "comments": { "data": [
+                        { "type": "comments", "id": "5" },
+                        { "type": "comments", "id": "12" }
+                    ] }
+                }
+            },
+            "included": [{
+                "type": "people",
+                "id": "9",
+                "attributes": { "first-name": "Dan" }
+            }, {
+                "type": "comments",
+                "id": "5",
+                "attributes": { "body": "First!" },
+                "relationships": { "author": { "data": null } }
+            }, {
+                "type": "comments",
+                "id": "12",
+                "attributes": { "body": "I like XML better" },
+                "relationships": { "author": { "data": { "type": "people", "id": "9" } } }
+            }]
+        })).unwrap()
+    }
+
+    fn dan() -> PersonView {
+        PersonView {
+            id: "9".into(),
+            first_name: "Dan".into(),
+        }
+    }
+
+    fn article() -> ArticleView {
+        ArticleView {
+            id: "1".into(),
+            title: "Rails is Omakase".into(),
+            author: dan(),
+            comments: vec![
+                CommentView { id: "5".into(), body: "First!".into(), author: None },
+                CommentView { id: "12".into(), body: "I like XML better".into(), author: Some(dan()) },
+            ],
+        }
+    }
+
+    #[test]
+    fn load() {
+        let loaded = document().load::<ArticleView>().unwrap();
+        assert_eq!(loaded, OptionalVec::One(Some(article())));
+
+        let mut d = document();
+        d.included.as_mut().unwrap().remove(0);
+        match d.load::<ArticleView>() {
+            Err(ObjectConversionError::UnresolvedRelationship { relationship, kind, id }) => {
+                assert_eq!(relationship, "author");
+                assert_eq!((kind.as_str(), id.as_str()), ("people", "9"));
+            },
+            other => panic!("expected an unresolved relationship, got {:?}", other),
+        }
+
+        assert!(matches!(document().load::<PersonView>(), Err(ObjectConversionError::ImproperType { .. })));
+    }
+
+    #[test]
+    fn dump() {
+        let d = Document::dump(&OptionalVec::One(Some(article()))).unwrap();
+        assert_eq!(d, document());
+        assert_eq!(d.load::<ArticleView>().unwrap(), OptionalVec::One(Some(article())));
+
+        let d = Document::dump(&OptionalVec::Many(vec![dan()])).unwrap();
+        assert_eq!(serde_json::to_value(&d).unwrap(), json!({
+            "data": [{ "type": "people", "id": "9", "attributes": { "first-name": "Dan" } }],
+        }));
+
+        let mut nameless = article();
+        nameless.author.id = String::new();
+        assert!(matches!(Document::dump(&OptionalVec::One(Some(nameless))), Err(ObjectConversionError::InvalidId{..})));
+    }
+
+    #[test]
+    fn dump_projections() {
+        let review = Review {
+            id: "1".into(),
+            author: dan(),
+            contact: PersonContact { id: "9".into(), email: "dan@example.com".into() },
+        };
+        let d = Document::dump(&OptionalVec::One(Some(review.clone()))).unwrap();
+        assert_eq!(serde_json::to_value(&d.included).unwrap(), json!([{
+            "type": "people",
+            "id": "9",
+            "attributes": { "first-name": "Dan", "email": "dan@example.com" },
+        }]));
+        assert_eq!(d.load::<Review>().unwrap(), OptionalVec::One(Some(review)));
+    }
+
+    #[test]
+    fn recursive() {
+        let thread = Thread {
+            id: "1".into(),
+            replies: vec![
+                Thread { id: "2".into(), replies: vec![Thread { id: "3".into(), replies: Vec::new() }] },
+            ],
+        };
+        let mut d = Document::dump(&OptionalVec::One(Some(thread.clone()))).unwrap();
+        assert_eq!(d.included.as_ref().unwrap().len(), 2);
+        assert_eq!(d.load::<Thread>().unwrap(), OptionalVec::One(Some(thread)));
+
+        let back = Relationship {
+            data: OptionalVec::Many(vec![Identifier::new("1".into(), "comments".into())]),
+            ..Default::default()
+        };
+        d.included.as_mut().unwrap()[1].relationships = Some(std::iter::once(("replies".to_string(), back)).collect());
+        assert!(matches!(d.load::<Thread>(), Err(ObjectConversionError::UnresolvedRelationship { .. })));
+    }
+}
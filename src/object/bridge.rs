@@ -0,0 +1,307 @@
+use serde::{
+    Serialize,
+    Serializer,
+    de::{Deserialize, DeserializeOwned, value::MapDeserializer},
+    ser::{
+        Error as _,
+        Impossible,
+        SerializeMap,
+        SerializeStruct,
+    },
+};
+use serde_json::{Error as SerdeError, Value};
+use crate::Map;
+
+#[cfg(not(feature = "raw-attributes"))]
+pub (crate) use self::parsed::*;
+#[cfg(feature = "raw-attributes")]
+pub (crate) use self::raw::*;
+
+/// Attributes parsed into a map of `Value`s
+#[cfg(not(feature = "raw-attributes"))]
+mod parsed {
+    use super::*;
+
+    /// How a `GenericObject` stores its attributes
+    pub type AttributeStorage = Map<String, Value>;
+
+    /// Deserializes `A` by handing out the entries of the map
+    pub fn from_attributes<A>(attributes: AttributeStorage) -> Result<A, SerdeError>
+    where A: DeserializeOwned {
+        from_map(attributes)
+    }
+
+    /// Deserializes `A` while borrowing the entries of the map
+    pub fn from_attributes_ref<'a, A>(attributes: &'a AttributeStorage) -> Result<A, SerdeError>
+    where A: Deserialize<'a> {
+        A::deserialize(MapDeserializer::new(attributes.iter().map(|(k, v)| (k.as_str(), v))))
+    }
+
+    /// Serializes `A` straight into an attribute map
+    ///
+    /// Produces `None` if `A` serializes to nothing (a unit or `None`)
+    pub fn to_attributes<A>(attributes: &A) -> Result<Option<AttributeStorage>, SerdeError>
+    where A: Serialize + ?Sized {
+        to_map(attributes)
+    }
+
+    pub fn storage_to_map(attributes: &AttributeStorage) -> Result<Map<String, Value>, SerdeError> {
+        Ok(attributes.clone())
+    }
+
+    pub fn map_to_storage(attributes: Map<String, Value>) -> AttributeStorage {
+        attributes
+    }
+}
+
+/// Attributes kept as the JSON text they were received as
+#[cfg(feature = "raw-attributes")]
+mod raw {
+    use super::*;
+    use serde_derive::{Serialize, Deserialize};
+    use serde_json::value::{RawValue, to_raw_value};
+
+    /// How a `GenericObject` stores its attributes
+    pub type AttributeStorage = RawAttributes;
+
+    /// Unparsed attributes, compared by their JSON text
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[serde(transparent)]
+    pub struct RawAttributes(Box<RawValue>);
+
+    impl PartialEq for RawAttributes {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.get() == other.0.get()
+        }
+    }
+
+    pub fn from_attributes<A>(attributes: AttributeStorage) -> Result<A, SerdeError>
+    where A: DeserializeOwned {
+        serde_json::from_str(attributes.0.get())
+    }
+
+    pub fn from_attributes_ref<'a, A>(attributes: &'a AttributeStorage) -> Result<A, SerdeError>
+    where A: Deserialize<'a> {
+        serde_json::from_str(attributes.0.get())
+    }
+
+    /// Produces `None` if `A` serializes to nothing (a unit or `None`)
+    pub fn to_attributes<A>(attributes: &A) -> Result<Option<AttributeStorage>, SerdeError>
+    where A: Serialize + ?Sized {
+        let raw = to_raw_value(attributes)?;
+        match raw.get() {
+            "null" => Ok(None),
+            s if s.starts_with('{') => Ok(Some(RawAttributes(raw))),
+            _ => not_a_map(),
+        }
+    }
+
+    pub fn storage_to_map(attributes: &AttributeStorage) -> Result<Map<String, Value>, SerdeError> {
+        serde_json::from_str(attributes.0.get())
+    }
+
+    pub fn map_to_storage(attributes: Map<String, Value>) -> AttributeStorage {
+        // A map of `Value`s with string keys always serializes
+        RawAttributes(to_raw_value(&attributes).unwrap())
+    }
+}
+
+/// Deserializes `A` by handing out the entries of the map
+pub (crate) fn from_map<A>(attributes: Map<String, Value>) -> Result<A, SerdeError>
+where A: DeserializeOwned {
+    A::deserialize(MapDeserializer::new(attributes.into_iter()))
+}
+
+/// Serializes `A` straight into a map of `Value`s
+///
+/// Produces `None` if `A` serializes to nothing (a unit or `None`)
+pub (crate) fn to_map<A>(attributes: &A) -> Result<Option<Map<String, Value>>, SerdeError>
+where A: Serialize + ?Sized {
+    attributes.serialize(AttributeSerializer)
+}
+
+struct AttributeSerializer;
+
+fn not_a_map<T>() -> Result<T, SerdeError> {
+    Err(SerdeError::custom("attributes must serialize to a map or struct"))
+}
+
+impl Serializer for AttributeSerializer {
+    type Ok = Option<Map<String, Value>>;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<Self::Ok, SerdeError>;
+    type SerializeTuple = Impossible<Self::Ok, SerdeError>;
+    type SerializeTupleStruct = Impossible<Self::Ok, SerdeError>;
+    type SerializeTupleVariant = Impossible<Self::Ok, SerdeError>;
+    type SerializeMap = AttributeMapSerializer;
+    type SerializeStruct = AttributeMapSerializer;
+    type SerializeStructVariant = Impossible<Self::Ok, SerdeError>;
+
+    fn serialize_none(self) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, SerdeError> {
+        Ok(None)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T)
+        -> Result<Self::Ok, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        Ok(AttributeMapSerializer { map: Map::new(), key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize)
+        -> Result<Self::SerializeStruct, SerdeError> {
+        Ok(AttributeMapSerializer { map: Map::new(), key: None })
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, SerdeError> { not_a_map() }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, SerdeError> { not_a_map() }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, _variant: &'static str)
+        -> Result<Self::Ok, SerdeError> {
+        not_a_map()
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32,
+        _variant: &'static str, _value: &T) -> Result<Self::Ok, SerdeError> {
+        not_a_map()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        not_a_map()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        not_a_map()
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize)
+        -> Result<Self::SerializeTupleStruct, SerdeError> {
+        not_a_map()
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str,
+        _len: usize) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        not_a_map()
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str,
+        _len: usize) -> Result<Self::SerializeStructVariant, SerdeError> {
+        not_a_map()
+    }
+}
+
+struct AttributeMapSerializer {
+    map: Map<String, Value>,
+    key: Option<String>,
+}
+
+impl SerializeMap for AttributeMapSerializer {
+    type Ok = Option<Map<String, Value>>;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        match serde_json::to_value(key)? {
+            Value::String(k) => {
+                self.key = Some(k);
+                Ok(())
+            },
+            _ => Err(SerdeError::custom("attribute names must be strings")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self.key.take()
+            .ok_or_else(|| SerdeError::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(key, serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(Some(self.map))
+    }
+}
+
+impl SerializeStruct for AttributeMapSerializer {
+    type Ok = Option<Map<String, Value>>;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T)
+        -> Result<(), SerdeError> {
+        self.map.insert(key.into(), serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, SerdeError> {
+        Ok(Some(self.map))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_derive::{Serialize, Deserialize};
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "kebab-case")]
+    struct Attr {
+        first_name: String,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        nested: Option<Value>,
+    }
+
+    fn map() -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("first-name".into(), json!("kitty"));
+        m.insert("nested".into(), json!({"a": [1]}));
+        m
+    }
+
+    fn attr() -> Attr {
+        Attr { first_name: "kitty".into(), nested: Some(json!({"a": [1]})) }
+    }
+
+    #[test]
+    fn deserialize() {
+        let storage = map_to_storage(map());
+        assert_eq!(from_attributes_ref::<Attr>(&storage).unwrap(), attr());
+        assert_eq!(from_attributes::<Attr>(storage).unwrap(), attr());
+        assert!(from_attributes::<Attr>(map_to_storage(Map::new())).is_err());
+        assert_eq!(from_map::<Attr>(map()).unwrap(), attr());
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(to_attributes(&attr()).unwrap(), Some(map_to_storage(map())));
+        assert_eq!(to_attributes(&map()).unwrap(), Some(map_to_storage(map())));
+        assert_eq!(to_map(&attr()).unwrap(), Some(map()));
+        assert_eq!(to_attributes(&()).unwrap(), None);
+        assert!(to_attributes(&1).is_err());
+    }
+}
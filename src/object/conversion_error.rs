@@ -1,8 +1,4 @@
-use serde_json::{
-    Error as SerdeError,
-    self,
-    Value,
-};
+use serde_json::Error as SerdeError;
 use std::{
     error::{Error as StdError},
     fmt::{Display, Formatter, Error as FormatError},
@@ -15,10 +11,23 @@ use std::{
 ///
 /// `ObjectConversionError::ImproperType` is produced when the type of the object does not
 /// match the output of the attribute object's `kind` function
+///
+/// `ObjectConversionError::UnknownType` is produced when the type of the object matches none
+/// of the variants of a `ResourceEnum`
+///
+/// `ObjectConversionError::InvalidId` is produced when the id of the object can not be
+/// parsed into the attribute object's `Id` type, `reason` holds the message of the parse error
+///
+/// `ObjectConversionError::UnresolvedRelationship` is produced when loading a `Model` reaches
+/// a related resource that is missing from the document, or that is already being loaded
+/// further up the model
 #[derive(Debug)]
 pub enum ObjectConversionError {
     FailedDeserialization(SerdeError),
     ImproperType{expected: String, got: String},
+    UnknownType{expected: Vec<String>, got: String},
+    InvalidId{id: String, reason: String},
+    UnresolvedRelationship{relationship: String, kind: String, id: String},
 }
 
 impl From<SerdeError> for ObjectConversionError {
@@ -35,6 +44,12 @@ impl Display for ObjectConversionError {
                 => write!(f, "Failed to Deserialize Attributes ({})", e),
             ObjectConversionError::ImproperType{expected, got} 
                 => write!(f, "Improper type (expected {}, got {})", expected, got),
+            ObjectConversionError::UnknownType{expected, got}
+                => write!(f, "Unknown type (expected one of {}, got {})", expected.join(", "), got),
+            ObjectConversionError::InvalidId{id, reason}
+                => write!(f, "Invalid id {} ({})", id, reason),
+            ObjectConversionError::UnresolvedRelationship{relationship, kind, id}
+                => write!(f, "Unresolved relationship {} (to {} {})", relationship, kind, id),
         }
     }
 }
@@ -44,6 +59,9 @@ impl StdError for ObjectConversionError {
         match self {
             ObjectConversionError::FailedDeserialization(e) => Some(e),
             ObjectConversionError::ImproperType{expected: _, got: _} => None,
+            ObjectConversionError::UnknownType{expected: _, got: _} => None,
+            ObjectConversionError::InvalidId{id: _, reason: _} => None,
+            ObjectConversionError::UnresolvedRelationship{relationship: _, kind: _, id: _} => None,
         }
     }
 }
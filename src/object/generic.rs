@@ -1,8 +1,7 @@
 use crate::{Meta, Relationships, Links, ResourceObject, Attributes, Identifier};
-use serde::{Serialize, de::DeserializeOwned};
+use serde::{ser::Serialize, de::DeserializeOwned};
 use serde_derive::{Serialize, Deserialize}; 
-use serde_json::{self, Value};
-use std::collections::BTreeMap;
+use super::bridge::{AttributeStorage, to_attributes, storage_to_map, map_to_storage};
 
 /// A generic resource object of some unknown type
 ///
@@ -16,10 +15,8 @@ pub struct GenericObject {
     pub (crate) id: String,
    #[serde(rename = "type")]
     pub (crate) kind: String,
-    // This should maybe be a serde_json::Map to make converting faster
-    // Alternately convert with a custom deserializer but also no,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub (crate) attributes: Option<BTreeMap<String, Value>>, 
+    pub (crate) attributes: Option<AttributeStorage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub (crate) relationships: Option<Relationships>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,19 +27,57 @@ pub struct GenericObject {
 
 impl GenericObject {
     /// Returns the type of the object (renamed here to `kind` due to keyword restrictuons)
-    pub fn kind<'a>(&'a self) -> &'a str {
+    pub fn kind(&self) -> &str {
         &self.kind
     }
+
+    /// Merges a newer copy of the same resource into this one
+    ///
+    /// Attributes, links and meta of `go` overwrite the ones already known, attributes it
+    /// leaves out are kept. Relationships are merged by name, a relationship without data
+    /// keeps the data already known
+    pub (crate) fn merge(&mut self, go: GenericObject) {
+        self.attributes = match (self.attributes.take(), go.attributes) {
+            (Some(old), Some(new)) => match (storage_to_map(&old), storage_to_map(&new)) {
+                (Ok(mut old), Ok(new)) => {
+                    old.extend(new);
+                    Some(map_to_storage(old))
+                },
+                _ => Some(new),
+            },
+            (old, new) => new.or(old),
+        };
+
+        if let Some(relationships) = go.relationships {
+            let existing = self.relationships.get_or_insert_with(Default::default);
+            for (name, mut r) in relationships {
+                if let Some(old) = existing.get(&name) {
+                    if r.data.is_not_present() {
+                        r.data = old.data.clone();
+                    }
+                    r.links = r.links.or_else(|| old.links.clone());
+                    r.meta = r.meta.or_else(|| old.meta.clone());
+                }
+                existing.insert(name, r);
+            }
+        }
+
+        if go.links.is_some() {
+            self.links = go.links;
+        }
+        if go.meta.is_some() {
+            self.meta = go.meta;
+        }
+    }
 }
 
 impl<A> From<ResourceObject<A>> for GenericObject
 where A: Attributes + Serialize + DeserializeOwned {
     fn from(ro: ResourceObject<A>) -> Self {
-        let v = serde_json::to_value(&ro.attributes).unwrap();
         Self {
-            id: ro.id,
+            id: ro.id.to_string(),
             kind: A::kind(),
-            attributes: serde_json::from_value(v).unwrap(),
+            attributes: to_attributes(&ro.attributes).unwrap(),
             relationships: ro.relationships,
             links: ro.links,
             meta: ro.meta,
@@ -53,11 +88,10 @@ where A: Attributes + Serialize + DeserializeOwned {
 impl<A> From<&ResourceObject<A>> for GenericObject 
 where A: Attributes + Serialize + DeserializeOwned {
     fn from(ro: &ResourceObject<A>) -> Self {
-        let v = serde_json::to_value(&ro.attributes).unwrap();
         Self {
-            id: ro.id.clone(),
+            id: ro.id.to_string(),
             kind: A::kind(),
-            attributes: serde_json::from_value(v).unwrap(),
+            attributes: to_attributes(&ro.attributes).unwrap(),
             relationships: ro.relationships.clone(),
             links: ro.links.clone(),
             meta: ro.meta.clone(),
@@ -94,7 +128,9 @@ impl From<&Identifier> for GenericObject {
 #[cfg(test)]
 mod test {
     use super::*;
-    use serde_json;
+    use crate::object::bridge::map_to_storage;
+    use serde_json::Value;
+    use crate::Map;
 
     #[test]
     fn serde_empty() {
@@ -117,7 +153,7 @@ mod test {
         let g1 = GenericObject {
             id: "a".into(),
             kind: "b".into(),
-            attributes: Some(BTreeMap::new()),
+            attributes: Some(map_to_storage(Map::new())),
             relationships: Some(Relationships::new()),
             links: Some(Links::new()),
             meta: Some(Meta::new()),
@@ -128,13 +164,22 @@ mod test {
         assert_eq!(g1, g2);
     }
 
+    #[cfg(feature = "raw-attributes")]
+    #[test]
+    fn serde_raw_attributes() {
+        let s = "{\"id\":\"a\",\"type\":\"b\",\"attributes\":{\"z\":1.50,\"a\":1e3}}";
+        let g : GenericObject = serde_json::from_str(s).unwrap();
+        assert_eq!(serde_json::to_string(&g).unwrap(), s);
+    }
+
     #[test]
     fn from_ro() {
         #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
         struct Attr {
             kitty: bool,
-        };
+        }
         impl Attributes for Attr {
+            type Id = String;
             fn kind() -> String { "b".into() }
         }
 
@@ -164,14 +209,14 @@ mod test {
         ro.links = Some(Links::new());
         ro.meta = Some(Meta::new());
 
-        let mut attr = BTreeMap::new();
+        let mut attr = Map::new();
         attr.insert("kitty".into(), Value::Bool(true));
 
         let go = (&ro).into();
         assert_eq!(GenericObject {
             id: "a".into(),
             kind: "b".into(),
-            attributes: Some(attr.clone()), 
+            attributes: Some(map_to_storage(attr.clone())),
             relationships: Some(Relationships::new()),
             links: Some(Links::new()),
             meta: Some(Meta::new()),
@@ -181,7 +226,7 @@ mod test {
         assert_eq!(GenericObject {
             id: "a".into(),
             kind: "b".into(),
-            attributes: Some(attr.clone()), 
+            attributes: Some(map_to_storage(attr.clone())),
             relationships: Some(Relationships::new()),
             links: Some(Links::new()),
             meta: Some(Meta::new()),
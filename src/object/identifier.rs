@@ -1,15 +1,22 @@
-use crate::{Meta, Relationships, Links, GenericObject, ResourceObject, Attributes};
-use serde::{Serialize, de::DeserializeOwned};
+use crate::{Meta, GenericObject, ResourceObject, Attributes};
+use serde::{ser::Serialize, de::DeserializeOwned};
 use serde_derive::{Serialize, Deserialize};
-use super::ObjectConversionError;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    hash::{Hash, Hasher},
+};
 
 /// A resource identifier object
 ///
 /// Basically just the identifying information needed to refer to a `ResourceObject`
 ///
+/// Identifiers are compared, hashed and ordered by their `type` and `id` only, `meta` is
+/// ignored
+///
 /// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-identifier-objects)
 /// for more information
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Identifier {
     /// The identifier of the object which, along with the object's type, MUST refer to a single
     /// unique resource
@@ -32,6 +39,39 @@ impl Identifier {
     }
 }
 
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.id == other.id
+    }
+}
+
+impl Eq for Identifier {}
+
+impl Hash for Identifier {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.kind.hash(state);
+        self.id.hash(state);
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.kind, &self.id).cmp(&(&other.kind, &other.id))
+    }
+}
+
+/// A set of resources, see `Document::identifiers`
+pub type IdentifierSet = BTreeSet<Identifier>;
+
+/// A map keyed by resource, see `Document::index`
+pub type IdentifierMap<V> = BTreeMap<Identifier, V>;
+
 impl From<GenericObject> for Identifier {
     fn from(go: GenericObject) -> Self {
         Self {
@@ -56,7 +96,7 @@ impl<A> From<ResourceObject<A>> for Identifier
 where A: Attributes + Serialize + DeserializeOwned {
     fn from(ro: ResourceObject<A>) -> Self {
         Self {
-            id: ro.id,
+            id: ro.id.to_string(),
             kind: A::kind(),
             meta: ro.meta,
         }
@@ -67,7 +107,7 @@ impl<A> From<&ResourceObject<A>> for Identifier
 where A: Attributes + Serialize + DeserializeOwned {
     fn from(ro: &ResourceObject<A>) -> Self {
         Self {
-            id: ro.id.clone(),
+            id: ro.id.to_string(),
             kind: A::kind(),
             meta: ro.meta.clone(),
         }
@@ -77,7 +117,6 @@ where A: Attributes + Serialize + DeserializeOwned {
 #[cfg(test)]
 mod test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde() {
@@ -92,6 +131,27 @@ mod test {
         assert_eq!(id1, id2);
     }
 
+    #[test]
+    fn identity() {
+        let mut meta = Meta::new();
+        meta.insert("c".into(), serde_json::json!("d"));
+        let a = Identifier::new("1".into(), "a".into());
+        let a_meta = Identifier { meta: Some(meta), ..a.clone() };
+        let b = Identifier::new("1".into(), "b".into());
+        let a2 = Identifier::new("2".into(), "a".into());
+        assert_eq!(a, a_meta);
+        assert_ne!(a, b);
+        assert!(a < a2 && a2 < b);
+
+        let set : IdentifierSet = vec![b.clone(), a_meta, a.clone()].into_iter().collect();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![a.clone(), b.clone()]);
+
+        let mut hashed = std::collections::HashSet::new();
+        assert!(hashed.insert(a.clone()));
+        assert!(!hashed.insert(Identifier::new("1".into(), "a".into())));
+        assert!(hashed.insert(b));
+    }
+
     #[test]
     fn from_go() {
         let mut meta = Meta::new();
@@ -105,19 +165,21 @@ mod test {
             meta: Some(meta.clone()),
         };
 
-        let id = (&go).into();
+        let id : Identifier = (&go).into();
         assert_eq!(Identifier {
             id: "a".into(),
             kind: "b".into(),
             meta: Some(meta.clone()),
         }, id);
+        assert_eq!(id.meta, Some(meta.clone()));
 
-        let id = go.into();
+        let id : Identifier = go.into();
         assert_eq!(Identifier {
             id: "a".into(),
             kind: "b".into(),
             meta: Some(meta.clone()),
         }, id);
+        assert_eq!(id.meta, Some(meta.clone()));
     }
 
     #[test] 
@@ -125,6 +187,7 @@ mod test {
         #[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
         struct Attr;
         impl Attributes for Attr{
+            type Id = String;
             fn kind() -> String { "b".into() }
         }
 
@@ -138,18 +201,20 @@ mod test {
             meta: Some(meta.clone()),
         };
 
-        let id = (&ro).into();
+        let id : Identifier = (&ro).into();
         assert_eq!(Identifier {
             id: "a".into(),
             kind: "b".into(),
             meta: Some(meta.clone()),
         }, id);
+        assert_eq!(id.meta, Some(meta.clone()));
 
-        let id = ro.into();
+        let id : Identifier = ro.into();
         assert_eq!(Identifier {
             id: "a".into(),
             kind: "b".into(),
             meta: Some(meta.clone()),
         }, id);
+        assert_eq!(id.meta, Some(meta.clone()));
     }
 }
@@ -1,3 +1,6 @@
+mod bridge;
+pub (crate) use bridge::{from_attributes_ref, map_to_storage, storage_to_map, AttributeStorage};
+
 mod generic;
 pub use generic::GenericObject;
 
@@ -5,7 +8,7 @@ mod resource;
 pub use resource::{ResourceObject, Attributes};
 
 mod identifier;
-pub use identifier::Identifier;
+pub use identifier::{Identifier, IdentifierSet, IdentifierMap};
 
 mod conversion_error;
 pub use conversion_error::ObjectConversionError;
@@ -1,8 +1,15 @@
-use crate::{Meta, Relationships, Relationship, Links, Link, GenericObject, Identifier};
+use crate::{Meta, Relationships, Relationship, RelationshipSchema, Links, Link, GenericObject, Identifier, Map, OptionalVec};
 use serde::{Serialize, de::DeserializeOwned};
-use serde_json::{Error, Value, self};
-use std::convert::{TryFrom, TryInto};
-use super::ObjectConversionError;
+use serde_json::{Error as SerdeError, Value};
+use std::{
+    convert::TryFrom,
+    fmt::Display,
+    str::FromStr,
+};
+use super::{
+    ObjectConversionError,
+    bridge::{from_attributes, from_attributes_ref, from_map, to_map, storage_to_map, map_to_storage},
+};
 
 /// A resource object
 ///
@@ -14,7 +21,7 @@ use super::ObjectConversionError;
 pub struct ResourceObject<A: Attributes + Serialize + DeserializeOwned> {
     /// The object identifier which together with `A::kind` must identify
     /// a unique resource
-    pub id: String,
+    pub id: A::Id,
     /// Attributes representing some of the resource's data
     pub attributes: Option<A>,
     /// Describes relationships between this resource and others
@@ -27,9 +34,9 @@ pub struct ResourceObject<A: Attributes + Serialize + DeserializeOwned> {
 
 impl<A> ResourceObject<A> 
 where A: Attributes + Serialize + DeserializeOwned {
-    pub fn new(id: String, attributes: Option<A>) -> Self {
+    pub fn new(id: A::Id, attributes: Option<A>) -> Self {
         Self {
-            links: A::links(&id),
+            links: A::links(&id.to_string()),
             id,
             attributes,
             relationships: None,
@@ -59,10 +66,112 @@ where A: Attributes + Serialize + DeserializeOwned {
             self.links = Some(l);
         }
     }
+
+    /// Produces a `GenericObject` containing only the attributes and relationships that
+    /// differ between `old` and `new`, suitable as the primary data of a `PATCH` request
+    ///
+    /// Attributes are compared by their serialized values. An attribute present in `old` but
+    /// missing from `new` is sent as `null`, relationships missing from `new` are left out
+    /// since there is no way to remove one through an update. Relationships are compared
+    /// along with the `meta` of their resource identifier objects
+    ///
+    /// Fails if the attributes don't serialize to a map with string keys
+    pub fn diff(old: &Self, new: &Self) -> Result<GenericObject, ObjectConversionError> {
+        let old_attributes = attribute_map(&old.attributes)?;
+        let new_attributes = attribute_map(&new.attributes)?;
+        let removed : Vec<_> = old_attributes.keys()
+            .filter(|k| !new_attributes.contains_key(*k))
+            .cloned()
+            .collect();
+        let mut attributes : Map<String, Value> = new_attributes.into_iter()
+            .filter(|(k, v)| old_attributes.get(k) != Some(v))
+            .collect();
+        attributes.extend(removed.into_iter().map(|k| (k, Value::Null)));
+
+        let mut relationships = Relationships::new();
+        if let Some(new_relationships) = &new.relationships {
+            for (k, r) in new_relationships {
+                let old = old.relationships.as_ref().and_then(|o| o.get(k));
+                if !old.is_some_and(|old| same_relationship(old, r)) {
+                    relationships.insert(k.clone(), r.clone());
+                }
+            }
+        }
+
+        Ok(GenericObject {
+            id: new.id.to_string(),
+            kind: A::kind(),
+            attributes: if attributes.is_empty() { None } else { Some(map_to_storage(attributes)) },
+            relationships: if relationships.is_empty() { None } else { Some(relationships) },
+            links: None,
+            meta: None,
+        })
+    }
+
+    /// Applies the primary data of a `PATCH` request to this resource
+    ///
+    /// Attributes in `patch` overwrite the stored ones, which are then deserialized back into
+    /// `A` so a partial update works even when `A` has required fields. Relationships in
+    /// `patch` replace the stored ones. Nothing is changed if an error is returned
+    ///
+    /// The id of `patch` is not checked, see `validate_update` for that
+    pub fn apply_patch(&mut self, patch: &GenericObject) -> Result<(), ObjectConversionError> {
+        if patch.kind != A::kind() {
+            return Err(ObjectConversionError::ImproperType{expected: A::kind(), got: patch.kind.clone()});
+        }
+        if let Some(patch_attributes) = &patch.attributes {
+            let mut attributes = attribute_map(&self.attributes)?;
+            attributes.extend(storage_to_map(patch_attributes)?);
+            let a : A = from_map(attributes)?;
+            self.attributes = Some(a);
+        }
+        if let Some(relationships) = &patch.relationships {
+            for (name, relationship) in relationships {
+                self.add_relationship(name.clone(), relationship.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares relationships including the `meta` of their resource identifier objects, which
+/// `Identifier` ignores
+fn same_relationship(a: &Relationship, b: &Relationship) -> bool {
+    let same = |a: &Identifier, b: &Identifier| a == b && a.meta == b.meta;
+    let data = match (&a.data, &b.data) {
+        (OptionalVec::NotPresent, OptionalVec::NotPresent) => true,
+        (OptionalVec::One(None), OptionalVec::One(None)) => true,
+        (OptionalVec::One(Some(a)), OptionalVec::One(Some(b))) => same(a, b),
+        (OptionalVec::Many(a), OptionalVec::Many(b)) =>
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b)),
+        _ => false,
+    };
+    data && a.links == b.links && a.meta == b.meta
+}
+
+fn parse_id<A: Attributes>(id: &str) -> Result<A::Id, ObjectConversionError> {
+    id.parse::<A::Id>().map_err(|e| ObjectConversionError::InvalidId{
+        id: id.into(),
+        reason: e.to_string(),
+    })
+}
+
+fn attribute_map<A: Serialize>(attributes: &Option<A>) -> Result<Map<String, Value>, SerdeError> {
+    Ok(to_map(attributes)?.unwrap_or_default())
 }
 
 /// Strictly type attributes of a `ResourceObject`
 pub trait Attributes {
+    /// The type of the `id` field of the resulting `ResourceObject`
+    ///
+    /// Ids are parsed with `FromStr` when converting into a `ResourceObject` and formatted
+    /// with `Display` when converting out of one. Use `String` to keep ids as they are,
+    /// which also allows the empty id of a resource being created without one
+    ///
+    /// There is no default, implementations written before `Id` was added need
+    /// `type Id = String;` to keep their ids as they were
+    type Id: FromStr<Err: Display> + Display;
+
     /// The `type` field of the resulting JSON:API `ResourceObject`
     ///
     /// This SHOULD NOT change between calls
@@ -70,9 +179,24 @@ pub trait Attributes {
 
     /// Called by `ResourceObject::new` with the intended id of the object
     /// to allow default links to be provided
-    fn links(id: &str) -> Option<Links> {
+    fn links(_id: &str) -> Option<Links> {
         None
     }
+
+    /// Whether clients may provide their own ids when creating resources of this type
+    ///
+    /// Used by `validate_create`, defaults to `false`
+    fn client_ids() -> bool {
+        false
+    }
+
+    /// The relationships resources of this type may have, by name, along with the types
+    /// they point to and how many
+    ///
+    /// Used by `Registry` and the schema generators for introspection, defaults to none
+    fn relationships() -> Vec<(String, RelationshipSchema)> {
+        Vec::new()
+    }
 }
 
 impl<A> TryFrom<GenericObject> for ResourceObject<A>
@@ -83,13 +207,12 @@ where A: Attributes + Serialize + DeserializeOwned {
             return Err(ObjectConversionError::ImproperType{expected: A::kind(), got: go.kind});
         }
         let attributes = if let Some(m) = go.attributes {
-            let v = Value::Object(m.into_iter().collect());
-            let a : A = serde_json::from_value(v)?;
+            let a : A = from_attributes(m)?;
             Some(a)
         } else { None };
 
         Ok(Self {
-            id: go.id,
+            id: parse_id::<A>(&go.id)?,
             attributes,
             relationships: go.relationships,
             links: go.links,
@@ -106,13 +229,12 @@ where A: Attributes + Serialize + DeserializeOwned {
             return Err(ObjectConversionError::ImproperType{expected: A::kind(), got: go.kind.clone()});
         }
         let attributes = if let Some(m) = &go.attributes {
-            let v = Value::Object(m.iter().map(|(k,v)| (k.clone(), v.clone())).collect());
-            let a : A = serde_json::from_value(v)?;
+            let a : A = from_attributes_ref(m)?;
             Some(a)
         } else { None };
 
         Ok( Self{
-            id: go.id.clone(),
+            id: parse_id::<A>(&go.id)?,
             attributes,
             relationships: go.relationships.clone(),
             links: go.links.clone(),
@@ -129,7 +251,7 @@ where A: Attributes + Serialize + DeserializeOwned {
             return Err(ObjectConversionError::ImproperType{expected: A::kind(), got: id.kind});
         }
         Ok(Self {
-            id: id.id,
+            id: parse_id::<A>(&id.id)?,
             attributes: None,
             relationships: None,
             links: None,
@@ -146,7 +268,7 @@ where A: Attributes + Serialize + DeserializeOwned {
             return Err(ObjectConversionError::ImproperType{expected: A::kind(), got: id.kind.clone()});
         }
         Ok(Self {
-            id: id.id.clone(),
+            id: parse_id::<A>(&id.id)?,
             attributes: None,
             relationships: None,
             links: None,
@@ -159,7 +281,7 @@ where A: Attributes + Serialize + DeserializeOwned {
 mod test {
     use super::*;
     use serde_derive::{Serialize, Deserialize};
-    use std::collections::BTreeMap;
+    use std::convert::TryInto;
 
     #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
     struct Attr {
@@ -167,17 +289,189 @@ mod test {
     }
 
     impl Attributes for Attr { 
+        type Id = String;
         fn kind() -> String { "b".into() }
     }
 
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Nested {
+        a: Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        b: Option<u32>,
+        c: String,
+    }
+
+    impl Attributes for Nested {
+        type Id = String;
+        fn kind() -> String { "n".into() }
+    }
+
+    #[test]
+    fn diff() {
+        let mut old = ResourceObject::new("1".into(), Some(Nested {
+            a: serde_json::json!({"x": [1, 2]}),
+            b: Some(1),
+            c: "c".into(),
+        }));
+        old.add_relationship("r".into(), Relationship::default());
+        old.add_relationship("s".into(), Relationship::default());
+
+        let go = ResourceObject::diff(&old, &old).unwrap();
+        assert_eq!(go, GenericObject {
+            id: "1".into(),
+            kind: "n".into(),
+            attributes: None,
+            relationships: None,
+            links: None,
+            meta: None,
+        });
+
+        let mut new = old.clone();
+        new.attributes = Some(Nested {
+            a: serde_json::json!({"x": [1, 3]}),
+            b: None,
+            c: "c".into(),
+        });
+        let r = Relationship {
+            data: crate::OptionalVec::One(None),
+            ..Default::default()
+        };
+        new.add_relationship("s".into(), r.clone());
+
+        let mut attr = Map::new();
+        attr.insert("a".into(), serde_json::json!({"x": [1, 3]}));
+        attr.insert("b".into(), Value::Null);
+        let mut relationships = Relationships::new();
+        relationships.insert("s".into(), r);
+
+        let go = ResourceObject::diff(&old, &new).unwrap();
+        assert_eq!(go, GenericObject {
+            id: "1".into(),
+            kind: "n".into(),
+            attributes: Some(map_to_storage(attr)),
+            relationships: Some(relationships),
+            links: None,
+            meta: None,
+        });
+    }
+
+    #[test]
+    fn diff_linkage_meta() {
+        let id = Identifier::new("2".into(), "m".into());
+        let mut old = ResourceObject::<Nested>::new("1".into(), None);
+        old.add_relationship("r".into(), Relationship {
+            data: OptionalVec::One(Some(id.clone())),
+            ..Default::default()
+        });
+
+        let mut meta = Meta::new();
+        meta.insert("role".into(), serde_json::json!("editor"));
+        let mut new = old.clone();
+        new.add_relationship("r".into(), Relationship {
+            data: OptionalVec::One(Some(Identifier { meta: Some(meta), ..id })),
+            ..Default::default()
+        });
+
+        let go = ResourceObject::diff(&old, &new).unwrap();
+        assert_eq!(go.relationships, new.relationships);
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Scalar(u32);
+
+    impl Attributes for Scalar {
+        type Id = String;
+        fn kind() -> String { "s".into() }
+    }
+
+    #[test]
+    fn not_a_map() {
+        let mut ro = ResourceObject::new("1".into(), Some(Scalar(1)));
+        assert!(matches!(ResourceObject::diff(&ro, &ro), Err(ObjectConversionError::FailedDeserialization(_))));
+
+        let patch = GenericObject {
+            id: "1".into(),
+            kind: "s".into(),
+            attributes: Some(map_to_storage(Map::new())),
+            relationships: None,
+            links: None,
+            meta: None,
+        };
+        assert!(ro.apply_patch(&patch).is_err());
+        assert_eq!(ro.attributes, Some(Scalar(1)));
+    }
+
+    #[test]
+    fn apply_patch() {
+        let mut ro = ResourceObject::new("1".into(), Some(Nested {
+            a: serde_json::json!({"x": [1, 2]}),
+            b: Some(1),
+            c: "c".into(),
+        }));
+        ro.add_relationship("r".into(), Relationship::default());
+
+        let mut attr = Map::new();
+        attr.insert("a".into(), serde_json::json!(true));
+        attr.insert("b".into(), Value::Null);
+        let r = Relationship {
+            data: crate::OptionalVec::One(None),
+            ..Default::default()
+        };
+        let mut relationships = Relationships::new();
+        relationships.insert("s".into(), r.clone());
+        let patch = GenericObject {
+            id: "1".into(),
+            kind: "n".into(),
+            attributes: Some(map_to_storage(attr)),
+            relationships: Some(relationships),
+            links: None,
+            meta: None,
+        };
+
+        ro.apply_patch(&patch).unwrap();
+        assert_eq!(ro.attributes, Some(Nested {
+            a: serde_json::json!(true),
+            b: None,
+            c: "c".into(),
+        }));
+        let mut relationships = Relationships::new();
+        relationships.insert("r".into(), Relationship::default());
+        relationships.insert("s".into(), r);
+        assert_eq!(ro.relationships, Some(relationships));
+    }
+
+    #[test]
+    fn apply_patch_fail() {
+        let mut ro = ResourceObject::new("1".into(), Some(Attr { kitty: true }));
+        let before = ro.clone();
+
+        let mut attr = Map::new();
+        attr.insert("kitty".into(), serde_json::json!("no"));
+        let mut patch = GenericObject {
+            id: "1".into(),
+            kind: "b".into(),
+            attributes: Some(map_to_storage(attr)),
+            relationships: Some(Relationships::new()),
+            links: None,
+            meta: None,
+        };
+        assert!(ro.apply_patch(&patch).is_err());
+        assert_eq!(ro, before);
+
+        patch.kind = "c".into();
+        patch.attributes = None;
+        assert!(ro.apply_patch(&patch).is_err());
+        assert_eq!(ro, before);
+    }
+
     #[test]
     fn from_go_success() {
-        let mut attr = BTreeMap::new();
+        let mut attr = Map::new();
         attr.insert("kitty".into(), Value::Bool(true));
         let go = GenericObject {
             id: "a".into(),
             kind: "b".into(),
-            attributes: Some(attr),
+            attributes: Some(map_to_storage(attr)),
             relationships: Some(Relationships::new()),
             links: Some(Links::new()),
             meta: Some(Meta::new()),
@@ -204,12 +498,12 @@ mod test {
 
     #[test]
     fn from_go_fail_type() {
-        let mut attr = BTreeMap::new();
+        let mut attr = Map::new();
         attr.insert("kitty".into(), Value::Bool(true));
         let go = GenericObject {
             id: "a".into(),
             kind: "c".into(),
-            attributes: Some(attr),
+            attributes: Some(map_to_storage(attr)),
             relationships: Some(Relationships::new()),
             links: Some(Links::new()),
             meta: Some(Meta::new()),
@@ -224,7 +518,7 @@ mod test {
         let go = GenericObject {
             id: "a".into(),
             kind: "b".into(),
-            attributes: Some(BTreeMap::new()),
+            attributes: Some(map_to_storage(Map::new())),
             relationships: None,
             links: None,
             meta: None,
@@ -261,6 +555,39 @@ mod test {
         }, ro);
     }
 
+    #[test]
+    fn typed_id() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+        struct Numbered;
+        impl Attributes for Numbered {
+            type Id = i64;
+            fn kind() -> String { "b".into() }
+            fn links(id: &str) -> Option<Links> {
+                let mut links = Links::new();
+                links.insert("self".into(), Link::Url(format!("/b/{}", id)));
+                Some(links)
+            }
+        }
+
+        let ro = ResourceObject::<Numbered>::new(-12, None);
+        assert_eq!(ro.links.as_ref().unwrap()["self"], Link::Url("/b/-12".into()));
+        let go = GenericObject::from(&ro);
+        assert_eq!(go.id, "-12");
+        assert_eq!(Identifier::from(&ro).id, "-12");
+        assert_eq!(ResourceObject::<Numbered>::try_from(&go).unwrap(), ro);
+        assert_eq!(ResourceObject::<Numbered>::try_from(Identifier::from(ro)).unwrap().id, -12);
+
+        let id = Identifier::new("a".into(), "b".into());
+        match ResourceObject::<Numbered>::try_from(&id) {
+            Err(ObjectConversionError::InvalidId{id, reason}) => {
+                assert_eq!(id, "a");
+                assert_eq!(reason, "invalid digit found in string");
+            },
+            r => panic!("expected an invalid id, got {:?}", r),
+        }
+        assert!(ResourceObject::<Numbered>::try_from(GenericObject::from(id)).is_err());
+    }
+
     #[test]
     fn from_id_fail() {
         let id = Identifier {
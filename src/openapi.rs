@@ -0,0 +1,282 @@
+use crate::Attributes;
+use crate::json_schema::{LINKS, add_definitions, create_schema, document_schema, reference, resource_schema};
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings, json_schema};
+use serde_json::{Map as JsonMap, Value, json};
+
+/// The media type of every JSON:API document
+const MEDIA_TYPE : &str = "application/vnd.api+json";
+/// The name of the schema shared by every error object
+const ERROR : &str = "JsonApiError";
+/// The name of the schema shared by every error document
+const ERROR_DOCUMENT : &str = "JsonApiErrorDocument";
+
+/// Builds the components of an OpenAPI 3.1 description for a set of resource types
+///
+/// Each type added with `add_resource` gets `{type}.Resource`, `{type}.Document`,
+/// `{type}.CollectionDocument` and `{type}.CreateDocument` schemas, along with responses and a
+/// request body of the same names using the `application/vnd.api+json` media type. Schemas
+/// for identifiers, relationships, links and errors, an `Error` response, and the standard
+/// `include`, `fields`, `sort` and `page` query parameters are always present
+///
+/// See the [OpenAPI docs](https://spec.openapis.org/oas/v3.1.0#components-object) for more
+/// information
+pub struct OpenApi {
+    title: String,
+    version: String,
+    generator: SchemaGenerator,
+    responses: JsonMap<String, Value>,
+    request_bodies: JsonMap<String, Value>,
+}
+
+impl OpenApi {
+    /// Starts a description of the API with the given title and version
+    pub fn new(title: String, version: String) -> Self {
+        let mut generator = SchemaSettings::draft2020_12()
+            .with(|s| {
+                s.definitions_path = "/components/schemas".into();
+                s.meta_schema = None;
+            })
+            .into_generator();
+        add_definitions(&mut generator);
+        add_error_definitions(&mut generator);
+
+        let mut responses = JsonMap::new();
+        responses.insert("Error".into(), json!({
+            "description": "A document containing errors",
+            "content": content(reference(&generator, ERROR_DOCUMENT)),
+        }));
+
+        Self {
+            title,
+            version,
+            generator,
+            responses,
+            request_bodies: JsonMap::new(),
+        }
+    }
+
+    /// Adds the components describing resources of type `A`
+    ///
+    /// Relationships are described by `A::relationships`, and each type they point to gets a
+    /// `{type}.Identifier` schema
+    pub fn add_resource<A: Attributes + JsonSchema>(&mut self) {
+        let kind = A::kind();
+        let name = |suffix: &str| format!("{}.{}", kind, suffix);
+
+        let resource = resource_schema::<A>(&mut self.generator, false);
+        let create = create_schema(resource_schema::<A>(&mut self.generator, true));
+        let resource_ref = reference(&self.generator, &name("Resource"));
+        let document = document_schema(&self.generator, json_schema!({
+            "oneOf": [resource_ref, {"type": "null"}],
+        }));
+        let collection = document_schema(&self.generator, json_schema!({
+            "type": "array",
+            "items": resource_ref,
+        }));
+
+        let schemas = vec![
+            ("Resource", resource),
+            ("Document", document),
+            ("CollectionDocument", collection),
+            ("CreateDocument", create),
+        ];
+        for (suffix, schema) in schemas {
+            self.generator.definitions_mut().insert(name(suffix), schema.to_value());
+        }
+
+        for suffix in &["Document", "CollectionDocument"] {
+            self.responses.insert(name(suffix), json!({
+                "description": format!("A {} document", kind),
+                "content": content(reference(&self.generator, &name(suffix))),
+            }));
+        }
+        self.request_bodies.insert(name("CreateDocument"), json!({
+            "description": format!("A document creating a {} resource", kind),
+            "required": true,
+            "content": content(reference(&self.generator, &name("CreateDocument"))),
+        }));
+    }
+
+    /// Produces the `openapi.json` document
+    pub fn to_value(&self) -> Value {
+        json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": self.title,
+                "version": self.version,
+            },
+            "paths": {},
+            "components": {
+                "schemas": self.generator.definitions(),
+                "responses": self.responses,
+                "requestBodies": self.request_bodies,
+                "parameters": parameters(),
+            },
+        })
+    }
+}
+
+fn content(schema: Schema) -> Value {
+    let mut content = JsonMap::new();
+    content.insert(MEDIA_TYPE.into(), json!({"schema": schema}));
+    content.into()
+}
+
+fn add_error_definitions(generator: &mut SchemaGenerator) {
+    let error = json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "links": reference(generator, LINKS),
+            "status": {"type": "string"},
+            "code": {"type": "string"},
+            "title": {"type": "string"},
+            "detail": {"type": "string"},
+            "source": {
+                "type": "object",
+                "properties": {
+                    "pointer": {"type": "string"},
+                    "parameter": {"type": "string"},
+                },
+            },
+            "meta": {"type": "object"},
+        },
+    });
+    let document = json!({
+        "type": "object",
+        "required": ["errors"],
+        "properties": {
+            "errors": {
+                "type": "array",
+                "items": reference(generator, ERROR),
+            },
+            "meta": {"type": "object"},
+            "jsonapi": {"type": "object"},
+            "links": reference(generator, LINKS),
+        },
+    });
+
+    let definitions = generator.definitions_mut();
+    definitions.insert(ERROR.into(), error);
+    definitions.insert(ERROR_DOCUMENT.into(), document);
+}
+
+/// The query parameters defined by the JSON:API spec
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#query-parameters) for more information
+fn parameters() -> Value {
+    json!({
+        "include": {
+            "name": "include",
+            "in": "query",
+            "description": "A comma separated list of relationship paths to include",
+            "style": "form",
+            "explode": false,
+            "schema": {"type": "string"},
+        },
+        "fields": {
+            "name": "fields",
+            "in": "query",
+            "description": "Comma separated lists of the fields to return for each type, as fields[type]",
+            "style": "deepObject",
+            "schema": {
+                "type": "object",
+                "additionalProperties": {"type": "string"},
+            },
+        },
+        "sort": {
+            "name": "sort",
+            "in": "query",
+            "description": "A comma separated list of fields to sort by, descending when prefixed with -",
+            "style": "form",
+            "explode": false,
+            "schema": {"type": "string"},
+        },
+        "page": {
+            "name": "page",
+            "in": "query",
+            "description": "Pagination parameters, as page[name]",
+            "style": "deepObject",
+            "schema": {
+                "type": "object",
+                "additionalProperties": {"type": "string"},
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::RelationshipSchema;
+    use serde_derive::{Serialize, Deserialize};
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct Article {
+        title: String,
+    }
+
+    impl Attributes for Article {
+        type Id = String;
+        fn kind() -> String { "articles".into() }
+        fn relationships() -> Vec<(String, RelationshipSchema)> {
+            vec![
+                ("author".into(), RelationshipSchema::to_one("people".into())),
+                ("tags".into(), RelationshipSchema::to_many("tags".into())),
+            ]
+        }
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct Person {
+        name: String,
+    }
+
+    impl Attributes for Person {
+        type Id = u64;
+        fn kind() -> String { "people".into() }
+    }
+
+    #[test]
+    fn components() {
+        let mut openapi = OpenApi::new("Blog".into(), "1.0".into());
+        openapi.add_resource::<Article>();
+        openapi.add_resource::<Person>();
+        let v = openapi.to_value();
+
+        assert_eq!(v["openapi"], "3.1.0");
+        assert_eq!(v["info"], json!({"title": "Blog", "version": "1.0"}));
+
+        let schemas = &v["components"]["schemas"];
+        for name in &["JsonApiResource", "JsonApiIdentifier", "JsonApiRelationship", "JsonApiLinks",
+            "JsonApiError", "JsonApiErrorDocument", "Article", "Person", "articles.Resource",
+            "articles.Document", "articles.CollectionDocument", "articles.CreateDocument",
+            "people.Resource", "people.Identifier", "tags.Identifier"] {
+            assert!(schemas[name].is_object(), "missing schema {}", name);
+        }
+        assert_eq!(schemas["articles.Resource"]["properties"]["attributes"],
+            json!({"$ref": "#/components/schemas/Article"}));
+        let relationships = &schemas["articles.Resource"]["properties"]["relationships"]["properties"];
+        assert_eq!(relationships["author"]["properties"]["data"]["oneOf"][1],
+            json!({"$ref": "#/components/schemas/people.Identifier"}));
+        assert_eq!(relationships["tags"]["properties"]["data"]["items"],
+            json!({"$ref": "#/components/schemas/tags.Identifier"}));
+        assert_eq!(schemas["people.Identifier"]["properties"]["type"], json!({"const": "people"}));
+        assert_eq!(schemas["people.CollectionDocument"]["properties"]["data"]["items"],
+            json!({"$ref": "#/components/schemas/people.Resource"}));
+        assert_eq!(schemas["JsonApiRelationship"]["properties"]["data"]["oneOf"][1],
+            json!({"$ref": "#/components/schemas/JsonApiIdentifier"}));
+
+        let responses = &v["components"]["responses"];
+        assert_eq!(responses["Error"]["content"][MEDIA_TYPE]["schema"],
+            json!({"$ref": "#/components/schemas/JsonApiErrorDocument"}));
+        assert!(responses["articles.Document"].is_object());
+        assert!(v["components"]["requestBodies"]["people.CreateDocument"]["content"][MEDIA_TYPE].is_object());
+
+        let parameters = &v["components"]["parameters"];
+        assert_eq!(parameters["fields"]["style"], "deepObject");
+        for name in &["include", "sort", "page"] {
+            assert_eq!(parameters[name]["in"], "query");
+        }
+    }
+}
@@ -1,6 +1,18 @@
-use serde_derive::{Serialize, Deserialize};
-use serde::de::{DeserializeOwned, Deserializer, Deserialize, Error};
-use serde_json::{Value, self};
+use serde_derive::Serialize;
+use serde::de::{
+    Deserializer,
+    Deserialize,
+    Error,
+    IntoDeserializer,
+    MapAccess,
+    SeqAccess,
+    Visitor,
+    value::{BorrowedBytesDeserializer, BorrowedStrDeserializer, MapAccessDeserializer},
+};
+use std::{
+    fmt::{self, Formatter},
+    marker::PhantomData,
+};
 
 /// A rather complicated relative of `Option<Vec>`
 ///
@@ -32,46 +44,115 @@ impl<T> OptionalVec<T> {
     ///
     /// Mostly provided to allow serialization to be skipped
     pub fn is_not_present(&self) -> bool {
-        match self {
-            OptionalVec::NotPresent => true,
-            _ => false,
-        }
+        matches!(self, OptionalVec::NotPresent)
     }
 
     /// Checks for the `OptionalVec::One` variant
     pub fn is_one(&self) -> bool {
-        match self {
-            OptionalVec::One(_) => true,
-            _ => false,
-        }
+        matches!(self, OptionalVec::One(_))
     }
 
     /// Checks for the `OptionalVec::Many` variant
     pub fn is_many(&self) -> bool {
-        match self {
-            OptionalVec::Many(_) => true,
-            _ => false,
-        }
+        matches!(self, OptionalVec::Many(_))
     }
 }
 
+/// Requires a self-describing format since the shape of the value decides the variant
 impl<'de, T> Deserialize<'de> for OptionalVec<T> 
-where T: DeserializeOwned {
+where T: Deserialize<'de> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> 
     where D: Deserializer<'de> {
-        // okay so this should probably use a visitor but that's like
-        //
-        // effort
-        //
-        // ya know?
-        let v = Value::deserialize(deserializer)?;
-        match serde_json::from_value::<Option<T>>(v.clone()) {
-            Ok(one) => Ok(OptionalVec::One(one)),
-            Err(_) => match serde_json::from_value(v) {
-                Ok(many) => Ok(OptionalVec::Many(many)),
-                Err(_) => Err(D::Error::custom("Neither one nor many")),
-            },
+        deserializer.deserialize_any(OptionalVecVisitor(PhantomData))
+    }
+}
+
+/// Dispatches on the shape of the value, `null` becomes `OptionalVec::One(None)`, arrays
+/// become `OptionalVec::Many`, and anything else is handed to `T`
+struct OptionalVecVisitor<T>(PhantomData<T>);
+
+impl<'de, T> OptionalVecVisitor<T>
+where T: Deserialize<'de> {
+    fn one<D>(deserializer: D) -> Result<OptionalVec<T>, D::Error>
+    where D: Deserializer<'de> {
+        T::deserialize(deserializer).map(|t| OptionalVec::One(Some(t)))
+    }
+}
+
+impl<'de, T> Visitor<'de> for OptionalVecVisitor<T>
+where T: Deserialize<'de> {
+    type Value = OptionalVec<T>;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "null, a single value, or an array of values")
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(OptionalVec::One(None))
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(OptionalVec::One(None))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
+    where S: SeqAccess<'de> {
+        // Cap the preallocation so a bogus size hint can't exhaust memory
+        let mut many = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(t) = seq.next_element()? {
+            many.push(t);
         }
+        Ok(OptionalVec::Many(many))
+    }
+
+    fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+    where M: MapAccess<'de> {
+        Self::one(MapAccessDeserializer::new(map))
+    }
+
+    fn visit_bool<E: Error>(self, v: bool) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_borrowed_str<E: Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Self::one(BorrowedStrDeserializer::new(v))
+    }
+
+    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
+    }
+
+    fn visit_borrowed_bytes<E: Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Self::one(BorrowedBytesDeserializer::new(v))
+    }
+
+    fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Self::one(v.into_deserializer())
     }
 }
 
@@ -85,6 +166,7 @@ impl<T> Default for OptionalVec<T> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use serde_derive::Deserialize;
 
     #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
     struct TestStruct {
@@ -134,6 +216,40 @@ mod test {
         let _ : TestStruct = serde_json::from_str("{\"inner\":\"oops i'm a string\"}").unwrap();
     }
 
+    #[test]
+    fn failed_deserialize_element() {
+        let e = serde_json::from_str::<TestStruct>("{\"inner\":[1,-2]}").unwrap_err();
+        assert!(e.to_string().contains("invalid value: integer `-2`"), "{}", e);
+    }
+
+    #[test]
+    fn deserialize_objects() {
+        #[derive(Deserialize, PartialEq, Eq, Debug)]
+        struct Inner {
+            a: String,
+        }
+
+        let ov : OptionalVec<Inner> = serde_json::from_str("{\"a\":\"b\"}").unwrap();
+        assert_eq!(ov, OptionalVec::One(Some(Inner{ a: "b".into() })));
+        let ov : OptionalVec<Inner> = serde_json::from_str("[{\"a\":\"b\"}]").unwrap();
+        assert_eq!(ov, OptionalVec::Many(vec![Inner{ a: "b".into() }]));
+        let ov : OptionalVec<&str> = serde_json::from_str("\"b\"").unwrap();
+        assert_eq!(ov, OptionalVec::One(Some("b")));
+    }
+
+    #[test]
+    fn deserialize_non_json() {
+        use serde::de::value::{Error as ValueError, SeqDeserializer, UnitDeserializer, U32Deserializer};
+
+        let ov = OptionalVec::<u32>::deserialize(UnitDeserializer::<ValueError>::new()).unwrap();
+        assert_eq!(ov, OptionalVec::One(None));
+        let ov = OptionalVec::<u32>::deserialize(U32Deserializer::<ValueError>::new(1)).unwrap();
+        assert_eq!(ov, OptionalVec::One(Some(1)));
+        let d = SeqDeserializer::<_, ValueError>::new(vec![1u32, 2].into_iter());
+        let ov = OptionalVec::<u32>::deserialize(d).unwrap();
+        assert_eq!(ov, OptionalVec::Many(vec![1, 2]));
+    }
+
     #[test]
     fn is() {
         let ov : OptionalVec<usize> = OptionalVec::NotPresent;
@@ -0,0 +1,190 @@
+use crate::{Document, Error, OptionalVec};
+
+/// Which resource in a document a `PointerTarget` refers to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ResourceLocation {
+    /// An index into the primary data, `0` when the primary data is a single object
+    Data(usize),
+    /// An index into the `included` array
+    Included(usize),
+}
+
+/// The location in a request document that an error's `source.pointer` refers to
+///
+/// Produced by `resolve_pointer`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PointerTarget {
+    /// The document as a whole (the empty pointer)
+    Root,
+    /// A top-level member of the document that isn't a resource, such as `/meta` or a
+    /// `/data` array
+    Member(String),
+    /// A resource object, or its `id` or `type`
+    Resource(ResourceLocation),
+    /// A single attribute of a resource object, or something nested inside of it
+    Attribute { resource: ResourceLocation, name: String },
+    /// A single relationship of a resource object, or something nested inside of it
+    Relationship { resource: ResourceLocation, name: String },
+    /// The pointer is missing, malformed, or refers to something not in the document
+    Unresolvable,
+}
+
+/// Resolves the `source.pointer` of an error against the request document it was
+/// produced for
+///
+/// Attribute and relationship names are not required to be present in the document
+/// since servers commonly point at required members the client left out
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#error-objects) for more information
+pub fn resolve_pointer(document: &Document, error: &Error) -> PointerTarget {
+    let pointer = match error.source.as_ref().and_then(|s| s.pointer.as_ref()) {
+        Some(p) => p,
+        None => return PointerTarget::Unresolvable,
+    };
+    if pointer.is_empty() {
+        return PointerTarget::Root;
+    }
+    if !pointer.starts_with('/') {
+        return PointerTarget::Unresolvable;
+    }
+    let tokens : Vec<String> = pointer[1..].split('/').map(unescape).collect();
+    let mut tokens = tokens.iter().map(String::as_str);
+
+    let (resource, rest) = match tokens.next() {
+        Some("data") => match &document.data {
+            OptionalVec::One(Some(_)) => (ResourceLocation::Data(0), tokens),
+            OptionalVec::Many(v) => match tokens.next() {
+                None => return PointerTarget::Member("data".into()),
+                Some(i) => match parse_index(i) {
+                    Some(i) if i < v.len() => (ResourceLocation::Data(i), tokens),
+                    _ => return PointerTarget::Unresolvable,
+                },
+            },
+            _ => return PointerTarget::Unresolvable,
+        },
+        Some("included") => match &document.included {
+            Some(v) => match tokens.next() {
+                None => return PointerTarget::Member("included".into()),
+                Some(i) => match parse_index(i) {
+                    Some(i) if i < v.len() => (ResourceLocation::Included(i), tokens),
+                    _ => return PointerTarget::Unresolvable,
+                },
+            },
+            None => return PointerTarget::Unresolvable,
+        },
+        Some(member) => return if tokens.next().is_none() && has_member(document, member) {
+            PointerTarget::Member(member.into())
+        } else {
+            PointerTarget::Unresolvable
+        },
+        None => return PointerTarget::Unresolvable,
+    };
+
+    resolve_in_resource(resource, rest)
+}
+
+fn resolve_in_resource<'a>(resource: ResourceLocation, mut tokens: impl Iterator<Item = &'a str>)
+    -> PointerTarget {
+    match tokens.next() {
+        None | Some("id") | Some("type") => PointerTarget::Resource(resource),
+        Some("attributes") => match tokens.next() {
+            Some(name) => PointerTarget::Attribute { resource, name: name.into() },
+            None => PointerTarget::Resource(resource),
+        },
+        Some("relationships") => match tokens.next() {
+            Some(name) => PointerTarget::Relationship { resource, name: name.into() },
+            None => PointerTarget::Resource(resource),
+        },
+        Some(_) => PointerTarget::Unresolvable,
+    }
+}
+
+fn has_member(document: &Document, member: &str) -> bool {
+    match member {
+        "errors" => document.errors.is_some(),
+        "meta" => document.meta.is_some(),
+        "jsonapi" => document.jsonapi.is_some(),
+        "links" => document.links.is_some(),
+        _ => false,
+    }
+}
+
+/// Array indices in a JSON pointer MUST NOT have leading zeros
+fn parse_index(token: &str) -> Option<usize> {
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ErrorSource, GenericObject, Identifier, Meta};
+
+    fn error(pointer: &str) -> Error {
+        Error {
+            source: Some(ErrorSource {
+                pointer: Some(pointer.into()),
+                parameter: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn object(id: &str) -> GenericObject {
+        Identifier::new(id.into(), "b".into()).into()
+    }
+
+    #[test]
+    fn single() {
+        let d = Document {
+            data: OptionalVec::One(Some(object("a"))),
+            meta: Some(Meta::new()),
+            ..Default::default()
+        };
+        assert_eq!(resolve_pointer(&d, &error("")), PointerTarget::Root);
+        assert_eq!(resolve_pointer(&d, &error("/data")),
+            PointerTarget::Resource(ResourceLocation::Data(0)));
+        assert_eq!(resolve_pointer(&d, &error("/data/type")),
+            PointerTarget::Resource(ResourceLocation::Data(0)));
+        assert_eq!(resolve_pointer(&d, &error("/data/attributes/first-name")),
+            PointerTarget::Attribute { resource: ResourceLocation::Data(0), name: "first-name".into() });
+        assert_eq!(resolve_pointer(&d, &error("/data/attributes/a~1b~0c/0")),
+            PointerTarget::Attribute { resource: ResourceLocation::Data(0), name: "a/b~c".into() });
+        assert_eq!(resolve_pointer(&d, &error("/data/relationships/author/data")),
+            PointerTarget::Relationship { resource: ResourceLocation::Data(0), name: "author".into() });
+        assert_eq!(resolve_pointer(&d, &error("/meta")), PointerTarget::Member("meta".into()));
+        assert_eq!(resolve_pointer(&d, &error("/links")), PointerTarget::Unresolvable);
+        assert_eq!(resolve_pointer(&d, &error("/data/0")), PointerTarget::Unresolvable);
+        assert_eq!(resolve_pointer(&d, &error("data")), PointerTarget::Unresolvable);
+    }
+
+    #[test]
+    fn many() {
+        let d = Document {
+            data: OptionalVec::Many(vec![object("a"), object("b")]),
+            included: Some(vec![object("c")]),
+            ..Default::default()
+        };
+        assert_eq!(resolve_pointer(&d, &error("/data")), PointerTarget::Member("data".into()));
+        assert_eq!(resolve_pointer(&d, &error("/data/1/id")),
+            PointerTarget::Resource(ResourceLocation::Data(1)));
+        assert_eq!(resolve_pointer(&d, &error("/included/0/attributes/body")),
+            PointerTarget::Attribute { resource: ResourceLocation::Included(0), name: "body".into() });
+        assert_eq!(resolve_pointer(&d, &error("/data/2")), PointerTarget::Unresolvable);
+        assert_eq!(resolve_pointer(&d, &error("/data/01")), PointerTarget::Unresolvable);
+        assert_eq!(resolve_pointer(&d, &error("/included/1")), PointerTarget::Unresolvable);
+    }
+
+    #[test]
+    fn no_pointer() {
+        let d : Document = Default::default();
+        assert_eq!(resolve_pointer(&d, &Default::default()), PointerTarget::Unresolvable);
+        assert_eq!(resolve_pointer(&d, &error("/data")), PointerTarget::Unresolvable);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::GenericObject;
+
+/// An enum of `ResourceObject`s, one variant per resource type, usually declared with
+/// `resource_enum!`
+///
+/// Useful when the primary data of a document mixes resources of different types
+pub trait ResourceEnum: Sized {
+    /// The `type` of every variant, in declaration order
+    fn kinds() -> Vec<String>;
+
+    /// The `type` of the variant held
+    fn kind(&self) -> String;
+}
+
+/// Declares an enum of `ResourceObject`s that can be converted from a `GenericObject` of
+/// any of its variants' types
+///
+/// Each variant holds the `ResourceObject` of the `Attributes` type it is declared with.
+/// Converting a `GenericObject` of an unlisted type produces
+/// `ObjectConversionError::UnknownType`
+///
+/// ```
+/// # use json_api::{Attributes, resource_enum};
+/// # use serde_derive::{Serialize, Deserialize};
+/// #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// struct Article { title: String }
+/// impl Attributes for Article {
+///     type Id = String;
+///     fn kind() -> String { "articles".into() }
+/// }
+///
+/// #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// struct Person { name: String }
+/// impl Attributes for Person {
+///     type Id = String;
+///     fn kind() -> String { "people".into() }
+/// }
+///
+/// resource_enum! {
+///     #[derive(Debug, Clone, PartialEq)]
+///     pub enum SearchResult {
+///         Article(Article),
+///         Person(Person),
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! resource_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident($attributes:ty)),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant($crate::ResourceObject<$attributes>)),*
+        }
+
+        impl $crate::ResourceEnum for $name {
+            fn kinds() -> ::std::vec::Vec<::std::string::String> {
+                ::std::vec![$(<$attributes as $crate::Attributes>::kind()),*]
+            }
+
+            fn kind(&self) -> ::std::string::String {
+                match self {
+                    $($name::$variant(_) => <$attributes as $crate::Attributes>::kind()),*
+                }
+            }
+        }
+
+        impl ::std::convert::TryFrom<$crate::GenericObject> for $name {
+            type Error = $crate::ObjectConversionError;
+            fn try_from(go: $crate::GenericObject) -> ::std::result::Result<Self, Self::Error> {
+                $(if go.kind() == <$attributes as $crate::Attributes>::kind() {
+                    return ::std::convert::TryFrom::try_from(go).map($name::$variant);
+                })*
+                ::std::result::Result::Err($crate::unknown_type::<Self>(&go))
+            }
+        }
+
+        impl ::std::convert::TryFrom<&$crate::GenericObject> for $name {
+            type Error = $crate::ObjectConversionError;
+            fn try_from(go: &$crate::GenericObject) -> ::std::result::Result<Self, Self::Error> {
+                $(if go.kind() == <$attributes as $crate::Attributes>::kind() {
+                    return ::std::convert::TryFrom::try_from(go).map($name::$variant);
+                })*
+                ::std::result::Result::Err($crate::unknown_type::<Self>(go))
+            }
+        }
+
+        impl ::std::convert::From<$name> for $crate::GenericObject {
+            fn from(e: $name) -> Self {
+                match e {
+                    $($name::$variant(ro) => ro.into()),*
+                }
+            }
+        }
+
+        impl ::std::convert::From<&$name> for $crate::GenericObject {
+            fn from(e: &$name) -> Self {
+                match e {
+                    $($name::$variant(ro) => ro.into()),*
+                }
+            }
+        }
+
+        impl ::std::convert::From<&$name> for $crate::Identifier {
+            fn from(e: &$name) -> Self {
+                match e {
+                    $($name::$variant(ro) => ro.into()),*
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn unknown_type<E: ResourceEnum>(go: &GenericObject) -> crate::ObjectConversionError {
+    crate::ObjectConversionError::UnknownType{expected: E::kinds(), got: go.kind().into()}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ResourceObject, Attributes, Identifier, ObjectConversionError};
+    use serde_derive::{Serialize, Deserialize};
+    use std::convert::TryFrom;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Article {
+        title: String,
+    }
+
+    impl Attributes for Article {
+        type Id = String;
+        fn kind() -> String { "articles".into() }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Person {
+        name: String,
+    }
+
+    impl Attributes for Person {
+        type Id = u32;
+        fn kind() -> String { "people".into() }
+    }
+
+    crate::resource_enum! {
+        #[derive(PartialEq, Debug, Clone)]
+        enum Search {
+            Article(Article),
+            Person(Person),
+        }
+    }
+
+    #[test]
+    fn convert() {
+        let article = ResourceObject::<Article>::new("a".into(), Some(Article { title: "t".into() }));
+        let person = ResourceObject::<Person>::new(1, Some(Person { name: "n".into() }));
+        let gos = vec![GenericObject::from(&article), GenericObject::from(&person)];
+
+        let results = gos.iter().map(Search::try_from).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(results, vec![Search::Article(article.clone()), Search::Person(person)]);
+        assert_eq!(results[1].kind(), "people");
+        assert_eq!(Search::kinds(), vec!["articles", "people"]);
+
+        let owned = gos.into_iter().map(Search::try_from).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(owned, results);
+        assert_eq!(GenericObject::from(&owned[0]), GenericObject::from(&article));
+        assert_eq!(Identifier::from(&owned[0]), Identifier::from(&article));
+        assert_eq!(GenericObject::from(owned[0].clone()), GenericObject::from(article));
+    }
+
+    #[test]
+    fn convert_fail() {
+        let go = GenericObject::from(Identifier::new("1".into(), "comments".into()));
+        match Search::try_from(&go) {
+            Err(ObjectConversionError::UnknownType{expected, got}) => {
+                assert_eq!(expected, vec!["articles", "people"]);
+                assert_eq!(got, "comments");
+            },
+            r => panic!("expected an unknown type, got {:?}", r),
+        }
+
+        let go = GenericObject::from(Identifier::new("a".into(), "people".into()));
+        assert!(matches!(Search::try_from(go), Err(ObjectConversionError::InvalidId{..})));
+    }
+}
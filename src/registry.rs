@@ -0,0 +1,262 @@
+use crate::{Attributes, GenericObject, ObjectConversionError, ResourceObject};
+use serde::{
+    forward_to_deserialize_any,
+    ser::Serialize,
+    de::{self, DeserializeOwned, Deserializer, Error as _, Visitor},
+};
+use serde_derive::{Serialize, Deserialize};
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    convert::TryFrom,
+    fmt,
+    marker::PhantomData,
+};
+
+/// A collection of resource types registered at runtime
+///
+/// Lets resources be handled by their `type` alone, without knowing the `Attributes` type
+/// they belong to at compile time. Types are listed in order of their `type`
+#[derive(Default)]
+pub struct Registry {
+    types: BTreeMap<String, RegisteredType>,
+}
+
+/// Describes a resource type known to a `Registry`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct TypeDescription {
+    /// The `type` of the resources, renamed here to kind due to keyword limitations
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The names of the resources' attributes, empty if they could not be determined
+    pub attributes: Vec<String>,
+    /// The names of the resources' relationships
+    pub relationships: Vec<String>,
+    /// Whether clients may provide their own ids when creating resources
+    pub client_ids: bool,
+}
+
+struct RegisteredType {
+    description: TypeDescription,
+    handler: Box<dyn Handler + Send + Sync>,
+}
+
+trait Handler {
+    fn validate(&self, go: &GenericObject) -> Result<(), ObjectConversionError>;
+    fn convert(&self, go: GenericObject) -> Result<Box<dyn Any>, ObjectConversionError>;
+}
+
+struct TypedHandler<A>(PhantomData<fn() -> A>);
+
+impl<A> Handler for TypedHandler<A>
+where A: Attributes + Serialize + DeserializeOwned + 'static {
+    fn validate(&self, go: &GenericObject) -> Result<(), ObjectConversionError> {
+        ResourceObject::<A>::try_from(go).map(|_| ())
+    }
+
+    fn convert(&self, go: GenericObject) -> Result<Box<dyn Any>, ObjectConversionError> {
+        ResourceObject::<A>::try_from(go).map(|ro| Box::new(ro) as Box<dyn Any>)
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the resource type described by `A`, replacing any type previously
+    /// registered under `A::kind`
+    ///
+    /// Attribute names are read from `A`'s `Deserialize` implementation, which only works
+    /// for structs
+    pub fn register<A>(&mut self)
+    where A: Attributes + Serialize + DeserializeOwned + 'static {
+        let description = TypeDescription {
+            kind: A::kind(),
+            attributes: attribute_names::<A>(),
+            relationships: A::relationships().into_iter().map(|(name, _)| name).collect(),
+            client_ids: A::client_ids(),
+        };
+        self.types.insert(A::kind(), RegisteredType {
+            description,
+            handler: Box::new(TypedHandler::<A>(PhantomData)),
+        });
+    }
+
+    /// Checks whether a type has been registered
+    pub fn contains(&self, kind: &str) -> bool {
+        self.types.contains_key(kind)
+    }
+
+    /// Lists the types that have been registered
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.types.keys().map(String::as_str)
+    }
+
+    /// Describes every type that has been registered
+    pub fn descriptions(&self) -> impl Iterator<Item = &TypeDescription> {
+        self.types.values().map(|t| &t.description)
+    }
+
+    /// Describes the type of `go`
+    pub fn describe(&self, go: &GenericObject) -> Result<&TypeDescription, ObjectConversionError> {
+        self.get(go).map(|t| &t.description)
+    }
+
+    /// Checks that `go` converts into the `ResourceObject` of its registered type
+    pub fn validate(&self, go: &GenericObject) -> Result<(), ObjectConversionError> {
+        self.get(go)?.handler.validate(go)
+    }
+
+    /// Converts `go` into the `ResourceObject` of its registered type
+    ///
+    /// The result can be downcast into a `ResourceObject<A>` of the `Attributes` type that
+    /// was registered
+    pub fn convert(&self, go: GenericObject) -> Result<Box<dyn Any>, ObjectConversionError> {
+        self.get(&go)?.handler.convert(go)
+    }
+
+    fn get(&self, go: &GenericObject) -> Result<&RegisteredType, ObjectConversionError> {
+        self.types.get(go.kind()).ok_or_else(|| ObjectConversionError::UnknownType {
+            expected: self.kinds().map(String::from).collect(),
+            got: go.kind().into(),
+        })
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.descriptions()).finish()
+    }
+}
+
+/// Reads the field names of a struct by asking it to deserialize from a deserializer that
+/// gives up as soon as it learns them
+fn attribute_names<A: DeserializeOwned>() -> Vec<String> {
+    let mut fields = None;
+    let _ = A::deserialize(FieldNames(&mut fields));
+    fields.unwrap_or_default().iter().map(|f| f.to_string()).collect()
+}
+
+struct FieldNames<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de, 'a> Deserializer<'de> for FieldNames<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(Self::Error::custom("attributes are not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str,
+        fields: &'static [&'static str], _visitor: V) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(Self::Error::custom("found the field names"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Identifier, Map, RelationshipSchema};
+    use crate::object::map_to_storage;
+    use serde_json::Value;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    #[serde(rename_all = "kebab-case")]
+    struct Article {
+        title: String,
+        word_count: u32,
+    }
+
+    impl Attributes for Article {
+        type Id = u64;
+        fn kind() -> String { "articles".into() }
+        fn relationships() -> Vec<(String, RelationshipSchema)> {
+            vec![("author".into(), RelationshipSchema::to_one("people".into()))]
+        }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Tag;
+
+    impl Attributes for Tag {
+        type Id = String;
+        fn kind() -> String { "tags".into() }
+        fn client_ids() -> bool { true }
+    }
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry.register::<Tag>();
+        registry.register::<Article>();
+        registry
+    }
+
+    fn article(id: &str, title: Value) -> GenericObject {
+        let mut attributes = Map::new();
+        attributes.insert("title".into(), title);
+        attributes.insert("word-count".into(), serde_json::json!(3));
+        let mut go = GenericObject::from(Identifier::new(id.into(), "articles".into()));
+        go.attributes = Some(map_to_storage(attributes));
+        go
+    }
+
+    #[test]
+    fn describe() {
+        let registry = registry();
+        assert_eq!(registry.kinds().collect::<Vec<_>>(), vec!["articles", "tags"]);
+        assert!(registry.contains("tags"));
+        assert!(!registry.contains("people"));
+
+        let description = registry.describe(&article("1", "a".into())).unwrap();
+        assert_eq!(description, &TypeDescription {
+            kind: "articles".into(),
+            attributes: vec!["title".into(), "word-count".into()],
+            relationships: vec!["author".into()],
+            client_ids: false,
+        });
+        let descriptions = registry.descriptions().collect::<Vec<_>>();
+        assert_eq!(descriptions[1].attributes, Vec::<String>::new());
+        assert!(descriptions[1].client_ids);
+        assert_eq!(serde_json::to_string(descriptions[1]).unwrap(),
+            "{\"type\":\"tags\",\"attributes\":[],\"relationships\":[],\"client_ids\":true}");
+    }
+
+    #[test]
+    fn validate() {
+        let registry = registry();
+        assert!(registry.validate(&article("1", "a".into())).is_ok());
+        assert!(matches!(registry.validate(&article("1", 1.into())),
+            Err(ObjectConversionError::FailedDeserialization(_))));
+        assert!(matches!(registry.validate(&article("a", "a".into())),
+            Err(ObjectConversionError::InvalidId{..})));
+
+        let go = GenericObject::from(Identifier::new("1".into(), "people".into()));
+        match registry.validate(&go) {
+            Err(ObjectConversionError::UnknownType{expected, got}) => {
+                assert_eq!(expected, vec!["articles", "tags"]);
+                assert_eq!(got, "people");
+            },
+            r => panic!("expected an unknown type, got {:?}", r),
+        }
+        assert!(registry.describe(&go).is_err());
+    }
+
+    #[test]
+    fn convert() {
+        let registry = registry();
+        let any = registry.convert(article("1", "a".into())).unwrap();
+        let ro = any.downcast::<ResourceObject<Article>>().unwrap();
+        assert_eq!(ro.id, 1);
+        assert_eq!(ro.attributes, Some(Article { title: "a".into(), word_count: 3 }));
+
+        let go = GenericObject::from(Identifier::new("a".into(), "tags".into()));
+        assert!(registry.convert(go).unwrap().is::<ResourceObject<Tag>>());
+    }
+}
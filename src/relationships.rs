@@ -1,15 +1,17 @@
 use serde_derive::{Serialize, Deserialize};
-use crate::{Links, Identifier, OptionalVec, Meta};
-use std::collections::BTreeMap;
+use crate::{Links, Identifier, IdentifierSet, OptionalVec, Meta, Map};
 
 /// A set of object relationships
 ///
 /// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-object-relationships)
 /// for more information
-pub type Relationships = BTreeMap<String, Relationship>;
+pub type Relationships = Map<String, Relationship>;
 
 /// A relationship object
 ///
+/// Relationships compare their data through `Identifier`, so the `meta` of resource
+/// identifier objects is ignored
+///
 /// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-object-relationships)
 /// for more information
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -35,10 +37,49 @@ impl Default for Relationship {
     }
 }
 
+/// A change requested of a to-many relationship through its relationship endpoint
+///
+/// Usually produced from a `RelationshipDocument` with `into_add`, `into_remove`, or
+/// `into_replace`
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#crud-updating-to-many-relationships)
+/// for more information
+#[derive(Debug, PartialEq, Clone)]
+pub enum ToManyOperation {
+    /// Add the members to the relationship unless they are already present
+    Add(Vec<Identifier>),
+    /// Remove the members from the relationship if they are present
+    Remove(Vec<Identifier>),
+    /// Replace every member of the relationship
+    Replace(Vec<Identifier>),
+}
+
+impl ToManyOperation {
+    /// Applies the operation to the current members of a relationship
+    ///
+    /// Members are matched on their `type` and `id` only
+    pub fn apply(self, members: &mut Vec<Identifier>) {
+        match self {
+            ToManyOperation::Add(add) => {
+                let mut present : IdentifierSet = members.iter().cloned().collect();
+                for id in add {
+                    if present.insert(id.clone()) {
+                        members.push(id);
+                    }
+                }
+            },
+            ToManyOperation::Remove(remove) => {
+                let remove : IdentifierSet = remove.into_iter().collect();
+                members.retain(|m| !remove.contains(m));
+            },
+            ToManyOperation::Replace(replace) => *members = replace,
+        }
+    }
+}
+
 #[cfg(test)]
 mod relationship_test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde_empty() {
@@ -67,10 +108,31 @@ mod relationship_test {
     }
 }
 
+#[cfg(test)]
+mod to_many_operation_test {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let a = Identifier::new("1".into(), "a".into());
+        let b = Identifier::new("2".into(), "a".into());
+        let c = Identifier::new("1".into(), "c".into());
+        let mut members = vec![a.clone()];
+
+        ToManyOperation::Add(vec![a.clone(), b.clone(), c.clone()]).apply(&mut members);
+        assert_eq!(members, vec![a.clone(), b.clone(), c.clone()]);
+
+        ToManyOperation::Remove(vec![a.clone(), c.clone()]).apply(&mut members);
+        assert_eq!(members, vec![b.clone()]);
+
+        ToManyOperation::Replace(vec![c.clone(), a.clone()]).apply(&mut members);
+        assert_eq!(members, vec![c, a]);
+    }
+}
+
 #[cfg(test)]
 mod relationships_test {
     use super::*;
-    use serde_json;
 
     #[test]
     fn serde() {
@@ -89,7 +151,7 @@ mod relationships_test {
         rs1.insert("b".into(), r2);
         let s = serde_json::to_string(&rs1).unwrap();
         assert_eq!(s, "{\"a\":{},\"b\":{\"links\":{},\"data\":[],\"meta\":{}}}");
-        let rs2 = serde_json::from_str(&s).unwrap();
+        let rs2 : Relationships = serde_json::from_str(&s).unwrap();
         assert_eq!(rs1, rs2);
     }
 }
@@ -0,0 +1,409 @@
+use crate::{Document, Error, GenericObject, Identifier, Map, OptionalVec, Relationships};
+use crate::object::storage_to_map;
+use crate::validation::{bad_parameter, bad_request, escape, finish};
+use serde_derive::{Serialize, Deserialize};
+use serde_json::Value;
+use std::fmt::{self, Display, Formatter};
+
+/// A description of the resource types an API serves, used to check documents beyond what
+/// the JSON:API spec requires
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct Schema {
+    /// Every known resource type, keyed by its `type`
+    pub types: Map<String, TypeSchema>,
+}
+
+/// The description of a single resource type
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct TypeSchema {
+    /// The attributes resources of this type may have
+    #[serde(default)]
+    pub attributes: Map<String, AttributeSchema>,
+    /// The relationships resources of this type may have
+    #[serde(default)]
+    pub relationships: Map<String, RelationshipSchema>,
+    /// The relationship paths that may be included when fetching resources of this type,
+    /// along with every path leading to them
+    #[serde(default)]
+    pub includes: Vec<String>,
+    /// The fields resources of this type may be sorted by
+    #[serde(default)]
+    pub sort: Vec<String>,
+}
+
+/// The description of a single attribute
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct AttributeSchema {
+    /// The JSON type of the attribute's value, renamed here to kind due to keyword limitations
+    #[serde(rename = "type")]
+    pub kind: AttributeType,
+    /// Whether the value may also be `null`
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+impl AttributeSchema {
+    pub fn new(kind: AttributeType) -> Self {
+        Self {
+            kind,
+            nullable: false,
+        }
+    }
+
+    /// Checks whether `value` is allowed for this attribute
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (self.kind, value) {
+            (AttributeType::Any, _) => true,
+            (_, Value::Null) => self.nullable,
+            (AttributeType::String, Value::String(_)) => true,
+            (AttributeType::Number, Value::Number(_)) => true,
+            (AttributeType::Integer, Value::Number(n)) => n.is_i64() || n.is_u64(),
+            (AttributeType::Boolean, Value::Bool(_)) => true,
+            (AttributeType::Array, Value::Array(_)) => true,
+            (AttributeType::Object, Value::Object(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The JSON type of an attribute's value
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Array,
+    Object,
+    /// Any value, including `null`
+    Any,
+}
+
+impl Display for AttributeType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AttributeType::String => "a string",
+            AttributeType::Number => "a number",
+            AttributeType::Integer => "an integer",
+            AttributeType::Boolean => "a boolean",
+            AttributeType::Array => "an array",
+            AttributeType::Object => "an object",
+            AttributeType::Any => "anything",
+        })
+    }
+}
+
+/// The description of a single relationship
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct RelationshipSchema {
+    /// The types the relationship may point to, any type is allowed when empty
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Whether the relationship points to one resource or many
+    pub cardinality: Cardinality,
+}
+
+impl RelationshipSchema {
+    /// A to-one relationship pointing to resources of type `target`
+    pub fn to_one(target: String) -> Self {
+        Self {
+            targets: vec![target],
+            cardinality: Cardinality::ToOne,
+        }
+    }
+
+    /// A to-many relationship pointing to resources of type `target`
+    pub fn to_many(target: String) -> Self {
+        Self {
+            targets: vec![target],
+            cardinality: Cardinality::ToMany,
+        }
+    }
+}
+
+/// How many resources a relationship points to
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#document-resource-object-linkage)
+/// for more information
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum Cardinality {
+    /// `null` or a single resource identifier
+    ToOne,
+    /// An array of resource identifiers
+    ToMany,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Checks the primary data and included resources of `document` against the schema
+    ///
+    /// Every resource must be of a known type and may only have the attributes and
+    /// relationships described for it. Attribute values must be of the described JSON type,
+    /// and relationship data must have the described cardinality and point to the described
+    /// types. Attributes and relationships that are left out are not an error, so partial
+    /// updates pass
+    ///
+    /// Produces `400 Bad Request` errors pointing at each offending member
+    pub fn validate(&self, document: &Document) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        match &document.data {
+            OptionalVec::One(Some(go)) => self.check_resource(go, "/data", &mut errors),
+            OptionalVec::Many(many) => for (i, go) in many.iter().enumerate() {
+                self.check_resource(go, &format!("/data/{}", i), &mut errors);
+            },
+            _ => (),
+        }
+        for (i, go) in document.included.iter().flatten().enumerate() {
+            self.check_resource(go, &format!("/included/{}", i), &mut errors);
+        }
+        finish(errors)
+    }
+
+    /// Checks the `include` query parameter of a request for resources of type `kind`
+    ///
+    /// Every comma separated path must be listed in the type's `includes` or lead to one that
+    /// is, since including `comments.author` also includes `comments`
+    ///
+    /// See the [JSON:API docs](https://jsonapi.org/format/#fetching-includes) for more
+    /// information
+    pub fn validate_include(&self, kind: &str, include: &str) -> Result<(), Vec<Error>> {
+        self.check_parameter(kind, include, "include", |t, path| t.includes.iter().any(|i| {
+            i == path || (i.starts_with(path) && i[path.len()..].starts_with('.'))
+        }))
+    }
+
+    /// Checks the `sort` query parameter of a request for resources of type `kind`
+    ///
+    /// Every comma separated field, with or without a leading `-`, must be listed in the
+    /// type's `sort`
+    ///
+    /// See the [JSON:API docs](https://jsonapi.org/format/#fetching-sorting) for more
+    /// information
+    pub fn validate_sort(&self, kind: &str, sort: &str) -> Result<(), Vec<Error>> {
+        self.check_parameter(kind, sort, "sort", |t, field| {
+            let field = field.strip_prefix('-').unwrap_or(field);
+            t.sort.iter().any(|s| s == field)
+        })
+    }
+
+    fn check_parameter<F>(&self, kind: &str, value: &str, parameter: &str, allowed: F)
+        -> Result<(), Vec<Error>>
+    where F: Fn(&TypeSchema, &str) -> bool {
+        let t = match self.types.get(kind) {
+            Some(t) => t,
+            None => return Err(vec![bad_parameter(format!("Unknown type {}", kind), parameter)]),
+        };
+        let errors = value.split(',')
+            .filter(|v| !v.is_empty() && !allowed(t, v))
+            .map(|v| bad_parameter(format!("Unsupported {} {} for type {}", parameter, v, kind),
+                parameter))
+            .collect();
+        finish(errors)
+    }
+
+    fn check_resource(&self, go: &GenericObject, pointer: &str, errors: &mut Vec<Error>) {
+        let t = match self.types.get(&go.kind) {
+            Some(t) => t,
+            None => {
+                errors.push(bad_request(format!("Unknown type {}", go.kind),
+                    &format!("{}/type", pointer)));
+                return;
+            },
+        };
+        if let Some(attributes) = &go.attributes {
+            match storage_to_map(attributes) {
+                Ok(attributes) => check_attributes(t, &attributes, pointer, errors),
+                Err(e) => errors.push(bad_request(format!("Invalid attributes ({})", e),
+                    &format!("{}/attributes", pointer))),
+            }
+        }
+        if let Some(relationships) = &go.relationships {
+            check_relationships(t, relationships, pointer, errors);
+        }
+    }
+}
+
+fn check_attributes(t: &TypeSchema, attributes: &Map<String, Value>, pointer: &str,
+    errors: &mut Vec<Error>) {
+    for (name, value) in attributes {
+        let pointer = format!("{}/attributes/{}", pointer, escape(name));
+        match t.attributes.get(name) {
+            None => errors.push(bad_request(format!("Unknown attribute {}", name), &pointer)),
+            Some(a) => if !a.accepts(value) {
+                errors.push(bad_request(format!("Attribute {} must be {}", name, a.kind), &pointer));
+            },
+        }
+    }
+}
+
+fn check_relationships(t: &TypeSchema, relationships: &Relationships, pointer: &str,
+    errors: &mut Vec<Error>) {
+    for (name, r) in relationships {
+        let pointer = format!("{}/relationships/{}", pointer, escape(name));
+        let schema = match t.relationships.get(name) {
+            Some(schema) => schema,
+            None => {
+                errors.push(bad_request(format!("Unknown relationship {}", name), &pointer));
+                continue;
+            },
+        };
+        match (&r.data, schema.cardinality) {
+            (OptionalVec::NotPresent, _) => (),
+            (OptionalVec::One(one), Cardinality::ToOne) => if let Some(id) = one {
+                check_target(schema, id, &format!("{}/data", pointer), errors);
+            },
+            (OptionalVec::Many(many), Cardinality::ToMany) => for (i, id) in many.iter().enumerate() {
+                check_target(schema, id, &format!("{}/data/{}", pointer, i), errors);
+            },
+            (_, Cardinality::ToOne) => errors.push(bad_request(
+                format!("Relationship {} must be null or a single resource identifier", name),
+                &format!("{}/data", pointer))),
+            (_, Cardinality::ToMany) => errors.push(bad_request(
+                format!("Relationship {} must be an array of resource identifiers", name),
+                &format!("{}/data", pointer))),
+        }
+    }
+}
+
+fn check_target(schema: &RelationshipSchema, id: &Identifier, pointer: &str,
+    errors: &mut Vec<Error>) {
+    if !schema.targets.is_empty() && !schema.targets.contains(&id.kind) {
+        errors.push(bad_request(
+            format!("Expected one of {}, got {}", schema.targets.join(", "), id.kind),
+            &format!("{}/type", pointer)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{resolve_pointer, PointerTarget, ResourceLocation};
+    use serde_json::json;
+
+    fn schema() -> Schema {
+        let mut articles = TypeSchema::default();
+        articles.attributes.insert("title".into(), AttributeSchema::new(AttributeType::String));
+        articles.attributes.insert("views".into(), AttributeSchema {
+            kind: AttributeType::Integer,
+            nullable: true,
+        });
+        articles.relationships.insert("author".into(), RelationshipSchema::to_one("people".into()));
+        articles.relationships.insert("comments".into(),
+            RelationshipSchema::to_many("comments".into()));
+        articles.includes = vec!["author".into(), "comments.author".into()];
+        articles.sort = vec!["title".into()];
+
+        let mut schema = Schema::new();
+        schema.types.insert("articles".into(), articles);
+        schema.types.insert("people".into(), TypeSchema::default());
+        schema
+    }
+
+    fn document(attributes: Value, relationships: Value) -> Document {
+        serde_json::from_value(json!({
+            "data": [{
+                "id": "1",
+                "type": "articles",
+                "attributes": attributes,
+                "relationships": relationships,
+            }],
+            "included": [{"id": "9", "type": "people"}],
+        })).unwrap()
+    }
+
+    // Sorted since the order of members depends on the preserve-order feature
+    fn pointers(errors: &[Error]) -> Vec<&str> {
+        let mut pointers : Vec<_> = errors.iter()
+            .map(|e| e.source.as_ref().unwrap().pointer.as_deref().unwrap())
+            .collect();
+        pointers.sort_unstable();
+        pointers
+    }
+
+    #[test]
+    fn serde() {
+        let s = serde_json::to_value(schema()).unwrap();
+        assert_eq!(s["types"]["articles"]["attributes"]["views"],
+            json!({"type": "integer", "nullable": true}));
+        assert_eq!(s["types"]["articles"]["relationships"]["comments"],
+            json!({"targets": ["comments"], "cardinality": "to-many"}));
+        let schema2 : Schema = serde_json::from_value(s).unwrap();
+        assert_eq!(schema(), schema2);
+
+        let t : TypeSchema = serde_json::from_str("{}").unwrap();
+        assert_eq!(t, TypeSchema::default());
+    }
+
+    #[test]
+    fn validate_valid() {
+        let d = document(
+            json!({"title": "a", "views": null}),
+            json!({
+                "author": {"data": {"id": "9", "type": "people"}},
+                "comments": {"data": [{"id": "5", "type": "comments"}]},
+            }));
+        assert!(schema().validate(&d).is_ok());
+        assert!(schema().validate(&document(json!({}), json!({"author": {"data": null}}))).is_ok());
+        assert!(schema().validate(&Document::default()).is_ok());
+    }
+
+    #[test]
+    fn validate_invalid() {
+        let mut d = document(
+            json!({"title": 1, "views": 1.5, "body": "b"}),
+            json!({
+                "author": {"data": [{"id": "9", "type": "people"}]},
+                "comments": {"data": [{"id": "5", "type": "people"}]},
+                "tags": {"data": []},
+            }));
+        d.included.as_mut().unwrap().push(GenericObject::from(
+            Identifier::new("1".into(), "tags".into())));
+
+        let errors = schema().validate(&d).unwrap_err();
+        assert_eq!(pointers(&errors), vec![
+            "/data/0/attributes/body",
+            "/data/0/attributes/title",
+            "/data/0/attributes/views",
+            "/data/0/relationships/author/data",
+            "/data/0/relationships/comments/data/0/type",
+            "/data/0/relationships/tags",
+            "/included/1/type",
+        ]);
+        assert!(errors.iter().all(|e| e.status == Some("400".into())));
+        assert!(errors.iter().any(|e| e.detail == Some("Attribute title must be a string".into())));
+        let included = errors.iter().find(|e| e.detail == Some("Unknown type tags".into())).unwrap();
+        assert_eq!(resolve_pointer(&d, included), PointerTarget::Resource(ResourceLocation::Included(1)));
+    }
+
+    #[test]
+    fn parameters() {
+        let schema = schema();
+        assert!(schema.validate_include("articles", "author,comments.author").is_ok());
+        assert!(schema.validate_include("articles", "comments").is_ok());
+        assert!(schema.validate_include("articles", "").is_ok());
+        assert!(schema.validate_sort("articles", "-title").is_ok());
+
+        assert!(schema.validate_include("articles", "comments.auth").is_err());
+        assert!(schema.validate_include("articles", "comments.author.posts").is_err());
+        let errors = schema.validate_include("articles", "author,tags").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].source.as_ref().unwrap().parameter, Some("include".into()));
+        assert!(schema.validate_sort("articles", "title,-views").is_err());
+        assert!(schema.validate_sort("people", "title").is_err());
+        assert!(schema.validate_include("tags", "author").is_err());
+    }
+
+    #[test]
+    fn any_type() {
+        let a = AttributeSchema::new(AttributeType::Any);
+        assert!(a.accepts(&Value::Null));
+        assert!(a.accepts(&json!([1])));
+        assert!(!AttributeSchema::new(AttributeType::Integer).accepts(&Value::Null));
+        assert!(AttributeSchema::new(AttributeType::Number).accepts(&json!(1)));
+    }
+}
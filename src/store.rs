@@ -0,0 +1,251 @@
+use crate::{
+    Attributes,
+    Document,
+    GenericObject,
+    Identifier,
+    IdentifierMap,
+    ObjectConversionError,
+    OptionalVec,
+    ResourceObject,
+};
+use serde::{ser::Serialize, de::DeserializeOwned};
+use std::convert::TryFrom;
+
+/// A client-side cache holding one canonical copy of every resource, keyed by its `type`
+/// and `id`
+///
+/// Resources are merged as documents are ingested, so the store always holds the most
+/// recent attributes along with every relationship seen so far
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Store {
+    resources: IdentifierMap<GenericObject>,
+}
+
+impl Store {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Merges the primary data and included resources of `document` into the store
+    pub fn ingest(&mut self, document: &Document) {
+        let data : &[GenericObject] = match &document.data {
+            OptionalVec::One(Some(go)) => std::slice::from_ref(go),
+            OptionalVec::Many(many) => many,
+            _ => &[],
+        };
+        for go in data.iter().chain(document.included.iter().flatten()) {
+            self.insert(go.clone());
+        }
+    }
+
+    /// Merges a single resource into the store
+    ///
+    /// Attributes, links and meta of `go` overwrite the ones already known, attributes it
+    /// leaves out are kept. Relationships are merged by name, a relationship without data
+    /// keeps the data already known
+    pub fn insert(&mut self, go: GenericObject) {
+        let id = Identifier::from(&go);
+        match self.resources.get_mut(&id) {
+            Some(existing) => existing.merge(go),
+            None => {
+                self.resources.insert(id, go);
+            },
+        }
+    }
+
+    /// Removes a resource from the store, returning it if it was known
+    pub fn remove(&mut self, id: &Identifier) -> Option<GenericObject> {
+        self.resources.remove(id)
+    }
+
+    /// Checks whether a resource is known
+    pub fn contains(&self, id: &Identifier) -> bool {
+        self.resources.contains_key(id)
+    }
+
+    /// The number of resources known
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Lists every resource known, in order of their identifier
+    pub fn objects(&self) -> impl Iterator<Item = &GenericObject> {
+        self.resources.values()
+    }
+
+    /// Looks up a resource of any type
+    pub fn get_object(&self, id: &Identifier) -> Option<&GenericObject> {
+        self.resources.get(id)
+    }
+
+    /// Looks up a resource of type `A::kind` and converts it
+    pub fn get<A>(&self, id: &A::Id) -> Result<Option<ResourceObject<A>>, ObjectConversionError>
+    where A: Attributes + Serialize + DeserializeOwned {
+        let id = Identifier::new(id.to_string(), A::kind());
+        self.resources.get(&id).map(ResourceObject::try_from).transpose()
+    }
+
+    /// Converts every known resource of type `A::kind`
+    pub fn all<A>(&self) -> Result<Vec<ResourceObject<A>>, ObjectConversionError>
+    where A: Attributes + Serialize + DeserializeOwned {
+        let kind = A::kind();
+        self.resources.values()
+            .filter(|go| go.kind == kind)
+            .map(ResourceObject::try_from)
+            .collect()
+    }
+
+    /// Follows the relationship `name` of the resource `id`, converting the related
+    /// resources the store knows of
+    ///
+    /// Related resources that have not been ingested yet are left out, see
+    /// `related_identifiers` to find them
+    pub fn related<A>(&self, id: &Identifier, name: &str)
+        -> Result<Vec<ResourceObject<A>>, ObjectConversionError>
+    where A: Attributes + Serialize + DeserializeOwned {
+        self.related_identifiers(id, name)
+            .iter()
+            .filter_map(|id| self.resources.get(id))
+            .map(ResourceObject::try_from)
+            .collect()
+    }
+
+    /// The identifiers of the relationship `name` of the resource `id`, empty if either is
+    /// unknown
+    pub fn related_identifiers(&self, id: &Identifier, name: &str) -> Vec<Identifier> {
+        let data = self.resources.get(id)
+            .and_then(|go| go.relationships.as_ref())
+            .and_then(|relationships| relationships.get(name))
+            .map(|r| &r.data);
+        match data {
+            Some(OptionalVec::One(Some(id))) => vec![id.clone()],
+            Some(OptionalVec::Many(many)) => many.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_derive::{Serialize, Deserialize};
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Article {
+        title: String,
+        #[serde(default)]
+        views: u32,
+    }
+
+    impl Attributes for Article {
+        type Id = u32;
+
+        fn kind() -> String {
+            "articles".into()
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Person {
+        name: String,
+    }
+
+    impl Attributes for Person {
+        type Id = String;
+
+        fn kind() -> String {
+            "people".into()
+        }
+    }
+
+    fn document(value: serde_json::Value) -> Document {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn ingest() {
+        let mut store = Store::new();
+        store.ingest(&document(json!({
+            "data": [{
+                "type": "articles",
+                "id": "1",
+                "attributes": { "title": "First", "views": 3 },
+                "relationships": {
+                    "author": { "data": { "type": "people", "id": "9" } },
+                    "comments": { "links": { "related": "/articles/1/comments" } }
+                }
+            }, {
+                "type": "articles",
+                "id": "2",
+                "attributes": { "title": "Second" }
+            }],
+            "included": [{
+                "type": "people",
+                "id": "9",
+                "attributes": { "name": "Dan" }
+            }]
+        })));
+        assert_eq!(store.len(), 3);
+
+        let article = store.get::<Article>(&1).unwrap().unwrap();
+        assert_eq!(article.attributes, Some(Article { title: "First".into(), views: 3 }));
+        assert!(store.get::<Article>(&3).unwrap().is_none());
+        assert!(store.get::<Person>(&"1".to_string()).unwrap().is_none());
+        assert_eq!(store.all::<Article>().unwrap().len(), 2);
+
+        let id = Identifier::new("1".into(), "articles".into());
+        let authors = store.related::<Person>(&id, "author").unwrap();
+        assert_eq!(authors[0].attributes.as_ref().unwrap().name, "Dan");
+        assert!(store.related::<Article>(&id, "author").is_err());
+        assert!(store.related_identifiers(&id, "comments").is_empty());
+
+        store.remove(&Identifier::new("9".into(), "people".into()));
+        assert!(store.related::<Person>(&id, "author").unwrap().is_empty());
+        assert_eq!(store.related_identifiers(&id, "author").len(), 1);
+    }
+
+    #[test]
+    fn merge() {
+        let mut store = Store::new();
+        store.ingest(&document(json!({
+            "data": {
+                "type": "articles",
+                "id": "1",
+                "attributes": { "title": "First", "views": 3 },
+                "relationships": {
+                    "author": { "data": { "type": "people", "id": "9" } },
+                    "comments": { "data": [{ "type": "comments", "id": "5" }] }
+                }
+            }
+        })));
+        store.ingest(&document(json!({
+            "data": {
+                "type": "articles",
+                "id": "1",
+                "attributes": { "views": 4 },
+                "relationships": {
+                    "author": { "links": { "related": "/articles/1/author" } },
+                    "comments": { "data": [] },
+                    "tags": { "data": [] }
+                },
+                "meta": { "fresh": true }
+            }
+        })));
+
+        let article = store.get::<Article>(&1).unwrap().unwrap();
+        assert_eq!(article.attributes, Some(Article { title: "First".into(), views: 4 }));
+        assert_eq!(article.meta.unwrap()["fresh"], true);
+
+        let relationships = article.relationships.unwrap();
+        assert_eq!(relationships.len(), 3);
+        assert_eq!(relationships["author"].data,
+            OptionalVec::One(Some(Identifier::new("9".into(), "people".into()))));
+        assert!(relationships["author"].links.is_some());
+        assert_eq!(relationships["comments"].data, OptionalVec::Many(Vec::new()));
+    }
+}
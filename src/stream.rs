@@ -0,0 +1,505 @@
+use crate::{
+    Attributes,
+    Document,
+    Error,
+    GenericObject,
+    JsonApi,
+    Links,
+    Meta,
+    ObjectConversionError,
+    OptionalVec,
+    ResourceObject,
+};
+use serde::{
+    Serialize,
+    de::{DeserializeOwned, Error as _, IgnoredAny},
+};
+use serde_json::{Deserializer, Error as SerdeError};
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+    marker::PhantomData,
+};
+#[cfg(feature = "async")]
+use futures_util::{
+    io::{AsyncWrite, AsyncWriteExt},
+    pin_mut,
+    stream::{Stream, StreamExt},
+};
+
+/// Where in a document a streamed resource was found
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DocumentSection {
+    /// The primary data
+    Data,
+    /// The `included` array
+    Included,
+}
+
+/// Reads the resources of a document one at a time instead of holding all of them in memory
+///
+/// Iterating yields every resource in `data` and `included` in the order they appear in the
+/// input. The other top-level members are kept as they are passed, so `meta`, `links`,
+/// `jsonapi` and `errors` return `None` until the reader has seen them and are all available
+/// once iteration is finished. Iteration stops at the first error
+///
+/// The input is read a byte at a time so it should usually be wrapped in a `BufReader`
+#[derive(Debug)]
+pub struct DocumentReader<R> {
+    reader: PeekRead<R>,
+    state: State,
+    errors: Option<Vec<Error>>,
+    meta: Option<Meta>,
+    jsonapi: Option<JsonApi>,
+    links: Option<Links>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    Start,
+    Member { first: bool },
+    Array { section: DocumentSection, first: bool },
+    Done,
+}
+
+impl<R: Read> DocumentReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: PeekRead { inner: reader, peeked: None },
+            state: State::Start,
+            errors: None,
+            meta: None,
+            jsonapi: None,
+            links: None,
+        }
+    }
+
+    /// Converts every streamed resource into a `ResourceObject<A>`
+    ///
+    /// Resources of any other type produce an `ObjectConversionError::ImproperType` which
+    /// can be filtered out when `included` mixes types
+    pub fn typed<A>(self) -> TypedDocumentReader<R, A>
+    where A: Attributes + Serialize + DeserializeOwned {
+        TypedDocumentReader { reader: self, attributes: PhantomData }
+    }
+
+    /// Non-standard meta information, if it has been read yet
+    pub fn meta(&self) -> Option<&Meta> {
+        self.meta.as_ref()
+    }
+
+    /// Links related to the primary data, if they have been read yet
+    pub fn links(&self) -> Option<&Links> {
+        self.links.as_ref()
+    }
+
+    /// Information about the version of JSON:API being used, if it has been read yet
+    pub fn jsonapi(&self) -> Option<&JsonApi> {
+        self.jsonapi.as_ref()
+    }
+
+    /// Any errors that were encountered, if they have been read yet
+    pub fn errors(&self) -> Option<&[Error]> {
+        self.errors.as_deref()
+    }
+
+    fn advance(&mut self) -> Result<Option<(DocumentSection, GenericObject)>, SerdeError> {
+        loop {
+            match self.state {
+                State::Start => {
+                    self.expect(b'{')?;
+                    self.state = State::Member { first: true };
+                    if self.peek_non_whitespace()? == Some(b'}') {
+                        self.finish()?;
+                    }
+                },
+                State::Member { first } => {
+                    if !first {
+                        match self.next_non_whitespace()? {
+                            Some(b',') => (),
+                            Some(b'}') => {
+                                self.finish()?;
+                                continue;
+                            },
+                            _ => return Err(SerdeError::custom("expected `,` or `}` after a document member")),
+                        }
+                    }
+                    self.peek_non_whitespace()?;
+                    let key : String = self.deserialize()?;
+                    self.expect(b':')?;
+                    self.state = State::Member { first: false };
+                    if let Some(found) = self.member(&key)? {
+                        return Ok(Some(found));
+                    }
+                },
+                State::Array { section, first } => {
+                    if self.peek_non_whitespace()? == Some(b']') {
+                        self.reader.peeked = None;
+                        self.state = State::Member { first: false };
+                        continue;
+                    }
+                    if !first {
+                        self.expect(b',')?;
+                    }
+                    self.state = State::Array { section, first: false };
+                    return Ok(Some((section, self.deserialize()?)));
+                },
+                State::Done => return Ok(None),
+            }
+        }
+    }
+
+    /// Reads the value of a top-level member, producing the resource if it was a single
+    /// primary resource
+    fn member(&mut self, key: &str) -> Result<Option<(DocumentSection, GenericObject)>, SerdeError> {
+        match key {
+            "data" => match self.peek_non_whitespace()? {
+                Some(b'[') => self.start_array(DocumentSection::Data),
+                Some(b'n') => {
+                    self.deserialize::<IgnoredAny>()?;
+                },
+                _ => return Ok(Some((DocumentSection::Data, self.deserialize()?))),
+            },
+            "included" => match self.peek_non_whitespace()? {
+                Some(b'[') => self.start_array(DocumentSection::Included),
+                _ => return Err(SerdeError::custom("expected `included` to be an array")),
+            },
+            "errors" => self.errors = Some(self.deserialize()?),
+            "meta" => self.meta = Some(self.deserialize()?),
+            "jsonapi" => self.jsonapi = Some(self.deserialize()?),
+            "links" => self.links = Some(self.deserialize()?),
+            _ => self.skip()?,
+        }
+        Ok(None)
+    }
+
+    fn start_array(&mut self, section: DocumentSection) {
+        self.reader.peeked = None;
+        self.state = State::Array { section, first: true };
+    }
+
+    /// Makes sure nothing but whitespace follows the document
+    fn finish(&mut self) -> Result<(), SerdeError> {
+        self.reader.peeked = None;
+        self.state = State::Done;
+        match self.peek_non_whitespace()? {
+            None => Ok(()),
+            Some(_) => Err(SerdeError::custom("trailing characters after the document")),
+        }
+    }
+
+    /// Deserializes a single value, which must not be a bare number since `serde_json` reads
+    /// one byte past those
+    fn deserialize<T: DeserializeOwned>(&mut self) -> Result<T, SerdeError> {
+        T::deserialize(&mut Deserializer::from_reader(&mut self.reader))
+    }
+
+    /// Skips over the value of an unknown member
+    fn skip(&mut self) -> Result<(), SerdeError> {
+        match self.peek_non_whitespace()? {
+            Some(b'-') | Some(b'0'..=b'9') => {
+                while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E')
+                    | Some(b'0'..=b'9') = self.reader.peek().map_err(SerdeError::io)? {
+                    self.reader.peeked = None;
+                }
+                Ok(())
+            },
+            _ => self.deserialize::<IgnoredAny>().map(|_| ()),
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), SerdeError> {
+        match self.next_non_whitespace()? {
+            Some(b) if b == expected => Ok(()),
+            _ => Err(SerdeError::custom(format!("expected `{}`", expected as char))),
+        }
+    }
+
+    fn next_non_whitespace(&mut self) -> Result<Option<u8>, SerdeError> {
+        let b = self.peek_non_whitespace()?;
+        self.reader.peeked = None;
+        Ok(b)
+    }
+
+    fn peek_non_whitespace(&mut self) -> Result<Option<u8>, SerdeError> {
+        loop {
+            match self.reader.peek().map_err(SerdeError::io)? {
+                Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => self.reader.peeked = None,
+                b => return Ok(b),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for DocumentReader<R> {
+    type Item = Result<(DocumentSection, GenericObject), SerdeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.advance();
+        if result.is_err() {
+            self.state = State::Done;
+        }
+        result.transpose()
+    }
+}
+
+/// Reads the resources of a document one at a time as `ResourceObject<A>`s
+///
+/// Created with `DocumentReader::typed`
+#[derive(Debug)]
+pub struct TypedDocumentReader<R, A> {
+    reader: DocumentReader<R>,
+    attributes: PhantomData<A>,
+}
+
+impl<R, A> TypedDocumentReader<R, A> {
+    /// The underlying reader, for access to the other top-level members
+    pub fn reader(&self) -> &DocumentReader<R> {
+        &self.reader
+    }
+}
+
+impl<R, A> Iterator for TypedDocumentReader<R, A>
+where R: Read, A: Attributes + Serialize + DeserializeOwned {
+    type Item = Result<(DocumentSection, ResourceObject<A>), ObjectConversionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.reader.next()? {
+            Ok((section, go)) => ResourceObject::try_from(go).map(|ro| (section, ro)),
+            Err(e) => Err(e.into()),
+        })
+    }
+}
+
+/// Writes a document whose primary data is an array of resources taken one at a time from
+/// `data`, so the whole array never has to be held in memory
+///
+/// The other members of `rest` are written after the primary data, its own `data` is ignored
+pub fn write_document<W, I>(mut writer: W, data: I, rest: Document) -> Result<(), SerdeError>
+where W: Write, I: IntoIterator, I::Item: Into<GenericObject> {
+    writer.write_all(DATA_START).map_err(SerdeError::io)?;
+    for (i, go) in data.into_iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",").map_err(SerdeError::io)?;
+        }
+        serde_json::to_writer(&mut writer, &go.into())?;
+    }
+    writer.write_all(&data_end(rest)?).map_err(SerdeError::io)?;
+    writer.flush().map_err(SerdeError::io)
+}
+
+/// Writes a document whose primary data is an array of resources taken one at a time from
+/// the stream `data`, so the whole array never has to be held in memory
+///
+/// The other members of `rest` are written after the primary data, its own `data` is ignored
+#[cfg(feature = "async")]
+pub async fn write_document_async<W, S>(writer: &mut W, data: S, rest: Document)
+    -> Result<(), SerdeError>
+where W: AsyncWrite + Unpin, S: Stream, S::Item: Into<GenericObject> {
+    pin_mut!(data);
+    writer.write_all(DATA_START).await.map_err(SerdeError::io)?;
+    let mut buf = Vec::new();
+    let mut first = true;
+    while let Some(go) = data.next().await {
+        buf.clear();
+        if !first {
+            buf.push(b',');
+        }
+        first = false;
+        serde_json::to_writer(&mut buf, &go.into())?;
+        writer.write_all(&buf).await.map_err(SerdeError::io)?;
+    }
+    writer.write_all(&data_end(rest)?).await.map_err(SerdeError::io)?;
+    writer.flush().await.map_err(SerdeError::io)
+}
+
+const DATA_START : &[u8] = b"{\"data\":[";
+
+/// Closes the primary data array and appends the other members of `rest`
+fn data_end(mut rest: Document) -> Result<Vec<u8>, SerdeError> {
+    rest.data = OptionalVec::NotPresent;
+    let mut end = serde_json::to_vec(&rest)?;
+    // `rest` always serializes to an object, so swap its `{` for the end of the array
+    if end.len() > 2 {
+        end[0] = b',';
+        end.insert(0, b']');
+    } else {
+        end = b"]}".to_vec();
+    }
+    Ok(end)
+}
+
+/// Lets the reader look at the next byte without taking it away from `serde_json`
+#[derive(Debug)]
+struct PeekRead<R> {
+    inner: R,
+    peeked: Option<u8>,
+}
+
+impl<R: Read> PeekRead<R> {
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        if self.peeked.is_none() {
+            let mut b = [0];
+            loop {
+                match self.inner.read(&mut b) {
+                    Ok(0) => return Ok(None),
+                    Ok(_) => break,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            self.peeked = Some(b[0]);
+        }
+        Ok(self.peeked)
+    }
+}
+
+impl<R: Read> Read for PeekRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.peeked {
+            Some(b) if !buf.is_empty() => {
+                buf[0] = b;
+                self.peeked = None;
+                Ok(1)
+            },
+            _ => self.inner.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Document, OptionalVec};
+    use serde_derive::{Serialize, Deserialize};
+
+    const DOCUMENT : &str = include_str!("../tests/article.json");
+
+    #[test]
+    fn article() {
+        let document : Document = serde_json::from_str(DOCUMENT).unwrap();
+        let mut reader = DocumentReader::new(DOCUMENT.as_bytes());
+        let streamed : Vec<_> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+
+        let mut expected = Vec::new();
+        if let OptionalVec::One(Some(go)) = document.data {
+            expected.push((DocumentSection::Data, go));
+        }
+        expected.extend(document.included.unwrap().into_iter().map(|go| (DocumentSection::Included, go)));
+        assert_eq!(streamed, expected);
+        assert!(reader.meta().is_none());
+    }
+
+    #[test]
+    fn members() {
+        let s = "{ \"meta\": {\"a\": 1}, \"x\": -1.5e3, \"data\" : [ {\"id\":\"1\",\"type\":\"b\"} ,\
+            {\"id\":\"2\",\"type\":\"b\"}], \"y\": [1, \"]\"], \"links\": {\"self\": \"/b\"},\
+            \"jsonapi\": {\"version\": \"1.0\"}, \"included\": [] }\n";
+        let mut reader = DocumentReader::new(s.as_bytes());
+        assert_eq!(reader.meta().map(|m| m["a"].clone()), None);
+
+        let (section, go) = reader.next().unwrap().unwrap();
+        assert_eq!(section, DocumentSection::Data);
+        assert_eq!(go.id, "1");
+        assert_eq!(reader.meta().map(|m| m["a"].clone()), Some(serde_json::json!(1)));
+        assert!(reader.links().is_none());
+
+        let (_, go) = reader.next().unwrap().unwrap();
+        assert_eq!(go.id, "2");
+        assert!(reader.next().is_none());
+        assert_eq!(reader.links().unwrap()["self"].href(), Some("/b"));
+        assert_eq!(reader.jsonapi().unwrap().version, Some("1.0".into()));
+        assert!(reader.errors().is_none());
+    }
+
+    #[test]
+    fn single_and_null() {
+        let s = "{\"data\":{\"id\":\"1\",\"type\":\"b\"}}";
+        assert_eq!(DocumentReader::new(s.as_bytes()).count(), 1);
+        let s = "{\"data\":null,\"errors\":[{}]}";
+        let mut reader = DocumentReader::new(s.as_bytes());
+        assert!(reader.next().is_none());
+        assert_eq!(reader.errors().unwrap().len(), 1);
+        assert_eq!(DocumentReader::new("{}".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn malformed() {
+        for s in &["", "[]", "{\"data\":[{\"id\":\"1\",\"type\":\"b\"},]}", "{\"data\":[]", "{} {}",
+            "{\"included\":null}", "{\"data\":[] \"meta\":{}}"] {
+            let mut reader = DocumentReader::new(s.as_bytes());
+            let last = reader.by_ref().last();
+            assert!(matches!(last, Some(Err(_))), "{}", s);
+            assert!(reader.next().is_none());
+        }
+    }
+
+    #[test]
+    fn write() {
+        let mut document : Document = serde_json::from_str(DOCUMENT).unwrap();
+        let data = match std::mem::take(&mut document.data) {
+            OptionalVec::One(Some(go)) => vec![go.clone(), go],
+            _ => panic!("expected a single resource"),
+        };
+
+        let mut written = Vec::new();
+        write_document(&mut written, data.clone(), document.clone()).unwrap();
+        let expected = Document {
+            data: OptionalVec::Many(data),
+            ..document
+        };
+        assert_eq!(serde_json::from_slice::<Document>(&written).unwrap(), expected);
+
+        let mut written = Vec::new();
+        write_document(&mut written, Vec::<GenericObject>::new(), Default::default()).unwrap();
+        assert_eq!(written, b"{\"data\":[]}");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn write_async() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+        struct Comment {
+            body: String,
+        }
+        impl Attributes for Comment {
+            type Id = String;
+            fn kind() -> String { "comments".into() }
+        }
+
+        let comments : Vec<_> = (0..3)
+            .map(|i| ResourceObject::new(i.to_string(), Some(Comment { body: "a".into() })))
+            .collect();
+        let rest = Document {
+            meta: Some(Meta::new()),
+            ..Default::default()
+        };
+
+        let mut written = futures_util::io::Cursor::new(Vec::new());
+        let stream = futures_util::stream::iter(comments.clone());
+        futures_executor::block_on(write_document_async(&mut written, stream, rest.clone())).unwrap();
+
+        let mut expected = Vec::new();
+        write_document(&mut expected, comments, rest).unwrap();
+        assert_eq!(written.into_inner(), expected);
+    }
+
+    #[test]
+    fn typed() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+        struct Comment {
+            body: String,
+        }
+        impl Attributes for Comment {
+            type Id = String;
+            fn kind() -> String { "comments".into() }
+        }
+
+        let comments : Vec<_> = DocumentReader::new(DOCUMENT.as_bytes())
+            .typed::<Comment>()
+            .filter_map(Result::ok)
+            .map(|(_, ro)| ro.attributes.unwrap().body)
+            .collect();
+        assert_eq!(comments, vec!["First!", "I like XML better"]);
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{Attributes, Cardinality, RelationshipSchema};
+use schemars::{JsonSchema, SchemaGenerator, generate::SchemaSettings};
+use serde_json::Value;
+use std::fmt::{self, Display, Formatter, Write};
+
+/// The shapes shared by every resource type, mirroring `Document`, `Link`, `Error` and
+/// friends
+const GENERIC : &str = r#"export type Meta = { [key: string]: unknown };
+
+export type Link = string | null | {
+  href?: string;
+  meta?: Meta;
+};
+
+export type Links = { [key: string]: Link };
+
+export interface Identifier<T extends string = string> {
+  type: T;
+  id: string;
+  meta?: Meta;
+}
+
+export interface Relationship<I extends Identifier = Identifier> {
+  data?: I | I[] | null;
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface ToOneRelationship<I extends Identifier = Identifier> {
+  data?: I | null;
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface ToManyRelationship<I extends Identifier = Identifier> {
+  data?: I[];
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface GenericResource {
+  type: string;
+  id: string;
+  attributes?: { [key: string]: unknown };
+  relationships?: { [key: string]: Relationship };
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface ErrorSource {
+  pointer?: string;
+  parameter?: string;
+}
+
+export interface Error {
+  id?: string;
+  links?: Links;
+  status?: string;
+  code?: string;
+  title?: string;
+  detail?: string;
+  source?: ErrorSource;
+  meta?: Meta;
+}
+
+export interface JsonApi {
+  version?: string;
+  meta?: Meta;
+}
+
+export interface Document<D = Resource | Resource[] | null> {
+  data?: D;
+  errors?: Error[];
+  meta?: Meta;
+  jsonapi?: JsonApi;
+  links?: Links;
+  included?: Resource[];
+}
+"#;
+
+/// Builds TypeScript definitions (a `.d.ts` file) for a set of resource types
+///
+/// Each type added with `add_resource` gets an interface for its attributes named after the
+/// `Attributes` type, along with `{name}Resource` and `{name}Identifier` types. `Resource`
+/// and `AnyIdentifier` are unions of every type added. Relationships point to the
+/// identifiers of their targets, or to `AnyIdentifier` when they have none. The generic `Document`, `Link`, `Error` and `Relationship` shapes are always
+/// included, so names from the `Attributes` types should not clash with them
+///
+/// Attribute types are read from the types' `JsonSchema` implementations, use `to_string`
+/// to produce the file
+pub struct TypeScript {
+    generator: SchemaGenerator,
+    resources: Vec<Resource>,
+}
+
+struct Resource {
+    kind: String,
+    name: String,
+    relationships: Vec<(String, RelationshipSchema)>,
+}
+
+impl TypeScript {
+    pub fn new() -> Self {
+        Self {
+            generator: SchemaSettings::draft2020_12().into_generator(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Adds the definitions describing resources of type `A`
+    ///
+    /// Relationships are described by `A::relationships`
+    pub fn add_resource<A: Attributes + JsonSchema>(&mut self) {
+        let _ = self.generator.subschema_for::<A>();
+        self.resources.push(Resource {
+            kind: A::kind(),
+            name: A::schema_name().into(),
+            relationships: A::relationships(),
+        });
+    }
+
+    /// The type of the identifiers a relationship may point to, using the `{name}Identifier`
+    /// of targets that have been added
+    fn identifier(&self, schema: &RelationshipSchema) -> String {
+        if schema.targets.is_empty() {
+            return "AnyIdentifier".into();
+        }
+        schema.targets.iter()
+            .map(|kind| match self.resources.iter().find(|r| &r.kind == kind) {
+                Some(r) => format!("{}Identifier", r.name),
+                None => format!("Identifier<{}>", literal(kind)),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+impl Default for TypeScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for TypeScript {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(GENERIC)?;
+
+        for (name, schema) in self.generator.definitions() {
+            writeln!(f)?;
+            if is_object(schema) {
+                writeln!(f, "export interface {} {}", name, ts_type(schema, 0))?;
+            } else {
+                writeln!(f, "export type {} = {};", name, ts_type(schema, 0))?;
+            }
+        }
+
+        for r in &self.resources {
+            writeln!(f)?;
+            writeln!(f, "export type {}Identifier = Identifier<{}>;", r.name, literal(&r.kind))?;
+            writeln!(f)?;
+            writeln!(f, "export interface {}Resource {{", r.name)?;
+            writeln!(f, "  type: {};", literal(&r.kind))?;
+            writeln!(f, "  id: string;")?;
+            writeln!(f, "  attributes?: {};", r.name)?;
+            if r.relationships.is_empty() {
+                writeln!(f, "  relationships?: {{ [key: string]: Relationship<AnyIdentifier> }};")?;
+            } else {
+                writeln!(f, "  relationships?: {{")?;
+                for (name, schema) in &r.relationships {
+                    let relationship = match schema.cardinality {
+                        Cardinality::ToOne => "ToOneRelationship",
+                        Cardinality::ToMany => "ToManyRelationship",
+                    };
+                    writeln!(f, "    {}?: {}<{}>;", property(name), relationship, self.identifier(schema))?;
+                }
+                writeln!(f, "  }};")?;
+            }
+            writeln!(f, "  links?: Links;")?;
+            writeln!(f, "  meta?: Meta;")?;
+            writeln!(f, "}}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "export type AnyIdentifier = {};", union(&self.resources, "Identifier", "Identifier"))?;
+        writeln!(f)?;
+        writeln!(f, "export type Resource = {};", union(&self.resources, "Resource", "GenericResource"))
+    }
+}
+
+fn union(resources: &[Resource], suffix: &str, empty: &str) -> String {
+    if resources.is_empty() {
+        return empty.into();
+    }
+    resources.iter().map(|r| format!("{}{}", r.name, suffix)).collect::<Vec<_>>().join(" | ")
+}
+
+fn is_object(schema: &Value) -> bool {
+    schema.get("type") == Some(&Value::from("object")) && schema.get("properties").is_some()
+}
+
+/// Converts a JSON Schema into the TypeScript type accepting the same values
+fn ts_type(schema: &Value, indent: usize) -> String {
+    let schema = match schema {
+        Value::Bool(true) => return "unknown".into(),
+        Value::Bool(false) => return "never".into(),
+        Value::Object(schema) => schema,
+        _ => return "unknown".into(),
+    };
+    if let Some(Value::String(r)) = schema.get("$ref") {
+        return r.rsplit('/').next().unwrap_or(r).into();
+    }
+    if let Some(c) = schema.get("const") {
+        return c.to_string();
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return values.iter().map(Value::to_string).collect::<Vec<_>>().join(" | ");
+    }
+    for (key, separator) in &[("oneOf", " | "), ("anyOf", " | "), ("allOf", " & ")] {
+        if let Some(Value::Array(schemas)) = schema.get(*key) {
+            return schemas.iter()
+                .map(|s| wrap(ts_type(s, indent)))
+                .collect::<Vec<_>>()
+                .join(separator);
+        }
+    }
+    match schema.get("type") {
+        Some(Value::String(t)) => single_type(t, schema, indent),
+        Some(Value::Array(types)) => types.iter()
+            .filter_map(Value::as_str)
+            .map(|t| single_type(t, schema, indent))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        _ if schema.contains_key("properties") => single_type("object", schema, indent),
+        _ => "unknown".into(),
+    }
+}
+
+fn single_type(t: &str, schema: &serde_json::Map<String, Value>, indent: usize) -> String {
+    match t {
+        "string" => "string".into(),
+        "integer" | "number" => "number".into(),
+        "boolean" => "boolean".into(),
+        "null" => "null".into(),
+        "array" => match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(items)), _) => format!("[{}]",
+                items.iter().map(|s| ts_type(s, indent)).collect::<Vec<_>>().join(", ")),
+            (_, Some(items)) => format!("{}[]", wrap(ts_type(items, indent))),
+            _ => "unknown[]".into(),
+        },
+        "object" => object_type(schema, indent),
+        _ => "unknown".into(),
+    }
+}
+
+fn object_type(schema: &serde_json::Map<String, Value>, indent: usize) -> String {
+    let additional = match schema.get("additionalProperties") {
+        None | Some(Value::Bool(true)) => Some("unknown".to_string()),
+        Some(Value::Bool(false)) => None,
+        Some(s) => Some(ts_type(s, indent + 1)),
+    };
+    let properties = match schema.get("properties") {
+        Some(Value::Object(properties)) if !properties.is_empty() => properties,
+        _ => return match additional {
+            Some(t) => format!("{{ [key: string]: {} }}", t),
+            None => "{}".into(),
+        },
+    };
+    let required = |name: &str| schema.get("required")
+        .and_then(Value::as_array)
+        .is_some_and(|r| r.iter().any(|n| n == name));
+
+    let padding = "  ".repeat(indent + 1);
+    let mut s = String::from("{\n");
+    for (name, property_schema) in properties {
+        let optional = if required(name) { "" } else { "?" };
+        // Writing to a `String` can't fail
+        let _ = writeln!(s, "{}{}{}: {};", padding, property(name), optional,
+            ts_type(property_schema, indent + 1));
+    }
+    if let Some(t) = additional.filter(|_| schema.contains_key("additionalProperties")) {
+        let _ = writeln!(s, "{}[key: string]: {};", padding, t);
+    }
+    s.push_str(&"  ".repeat(indent));
+    s.push('}');
+    s
+}
+
+/// Parenthesizes unions and intersections so they can be used in an array or another union
+fn wrap(t: String) -> String {
+    if t.contains(" | ") || t.contains(" & ") { format!("({})", t) } else { t }
+}
+
+fn literal(s: &str) -> String {
+    Value::from(s).to_string()
+}
+
+fn property(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if valid { name.into() } else { literal(name) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_derive::{Serialize, Deserialize};
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    #[serde(rename_all = "kebab-case")]
+    struct Article {
+        title: String,
+        word_count: Option<u32>,
+        tags: Vec<String>,
+        status: Status,
+        extra: crate::Map<String, bool>,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    enum Status {
+        Draft,
+        Published,
+    }
+
+    impl Attributes for Article {
+        type Id = String;
+        fn kind() -> String { "articles".into() }
+        fn relationships() -> Vec<(String, RelationshipSchema)> {
+            vec![
+                ("author".into(), RelationshipSchema::to_one("people".into())),
+                ("pinned-comment".into(), RelationshipSchema::to_one("comments".into())),
+                ("related".into(), RelationshipSchema::to_many("articles".into())),
+                ("mentions".into(), RelationshipSchema {
+                    targets: Vec::new(),
+                    cardinality: Cardinality::ToMany,
+                }),
+            ]
+        }
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    struct Person {
+        name: String,
+    }
+
+    impl Attributes for Person {
+        type Id = u64;
+        fn kind() -> String { "people".into() }
+    }
+
+    #[test]
+    fn definitions() {
+        let mut ts = TypeScript::new();
+        ts.add_resource::<Article>();
+        ts.add_resource::<Person>();
+        let s = ts.to_string();
+
+        assert!(s.starts_with(GENERIC));
+        // Properties of the attributes are in the order of their schema, which depends on
+        // the preserve-order feature
+        assert!(s.contains("export interface Article {\n"));
+        for line in &["  title: string;\n", "  \"word-count\"?: number | null;\n",
+            "  tags: string[];\n", "  status: Status;\n", "  extra: { [key: string]: boolean };\n"] {
+            assert!(s.contains(line), "missing {} in {}", line, s);
+        }
+        assert!(s.contains("export type Status = \"Draft\" | \"Published\";\n"), "{}", s);
+        assert!(s.contains(concat!(
+            "export interface ArticleResource {\n",
+            "  type: \"articles\";\n",
+            "  id: string;\n",
+            "  attributes?: Article;\n",
+            "  relationships?: {\n",
+            "    author?: ToOneRelationship<PersonIdentifier>;\n",
+            "    \"pinned-comment\"?: ToOneRelationship<Identifier<\"comments\">>;\n",
+            "    related?: ToManyRelationship<ArticleIdentifier>;\n",
+            "    mentions?: ToManyRelationship<AnyIdentifier>;\n",
+            "  };\n")), "{}", s);
+        assert!(s.contains("export type PersonIdentifier = Identifier<\"people\">;\n"));
+        assert!(s.contains("  relationships?: { [key: string]: Relationship<AnyIdentifier> };\n"));
+        assert!(s.contains("export type AnyIdentifier = ArticleIdentifier | PersonIdentifier;\n"));
+        assert!(s.ends_with("export type Resource = ArticleResource | PersonResource;\n"));
+    }
+
+    #[test]
+    fn empty() {
+        let s = TypeScript::new().to_string();
+        assert!(s.ends_with("export type AnyIdentifier = Identifier;\n\nexport type Resource = GenericResource;\n"));
+    }
+
+    #[test]
+    fn schemas() {
+        use serde_json::json;
+        assert_eq!(ts_type(&json!({"type": "array", "items": {"type": ["string", "null"]}}), 0),
+            "(string | null)[]");
+        assert_eq!(ts_type(&json!({"type": "array", "prefixItems": [{"type": "integer"}, true]}), 0),
+            "[number, unknown]");
+        assert_eq!(ts_type(&json!({"allOf": [{"$ref": "#/$defs/A"}, {"$ref": "#/$defs/B"}]}), 0),
+            "A & B");
+        assert_eq!(ts_type(&json!({"type": "object"}), 0), "{ [key: string]: unknown }");
+        assert_eq!(ts_type(&json!({"type": "object", "additionalProperties": false}), 0), "{}");
+        assert_eq!(ts_type(&json!({"type": "object", "properties": {"a": {"const": 1}}, "required": ["a"]}), 1),
+            "{\n    a: 1;\n  }");
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{CreateDocument, Document, Error, ErrorSource, GenericObject, OptionalVec, Attributes, Relationships};
+use serde::{Serialize, de::DeserializeOwned};
+use crate::object::from_attributes_ref;
+
+/// Checks a request document sent to create a resource of type `A`
+///
+/// The primary data must be a single resource object whose `type` matches `A::kind`. An
+/// `id` is only accepted if `A::client_ids` allows it and it parses into `A::Id`, and any
+/// attributes must deserialize into `A`. Only a `CreateDocument` may leave out the `id`
+///
+/// Produces `409 Conflict` for a type mismatch, `403 Forbidden` for an unsupported client
+/// generated id, and `400 Bad Request` for anything else that is malformed
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#crud-creating) for more information
+pub fn validate_create<A>(document: &CreateDocument) -> Result<(), Vec<Error>>
+where A: Attributes + Serialize + DeserializeOwned {
+    let go = single_resource(&document.data)?;
+    let mut errors = Vec::new();
+
+    check_type::<A>(go, &mut errors);
+    if !go.id.is_empty() {
+        if !A::client_ids() {
+            errors.push(forbidden("Client-generated ids are not supported".into(), "/data/id"));
+        } else if let Err(e) = go.id.parse::<A::Id>() {
+            errors.push(bad_request(format!("Invalid id ({})", e), "/data/id"));
+        }
+    }
+    if let Some(attributes) = &go.attributes {
+        if let Err(e) = from_attributes_ref::<A>(attributes) {
+            errors.push(bad_request(format!("Invalid attributes ({})", e), "/data/attributes"));
+        }
+    }
+    check_relationships(&go.relationships, &mut errors);
+
+    finish(errors)
+}
+
+/// Checks a request document sent to update the resource of type `A` with the id
+/// `expected_id`
+///
+/// The primary data must be a single resource object whose `type` matches `A::kind` and
+/// whose `id` parses into an `A::Id` equal to `expected_id`, so ids are compared the way
+/// `A::Id` compares them. Attributes are not checked against `A` since an update may only
+/// contain some of them
+///
+/// Produces `409 Conflict` for a type or id mismatch and `400 Bad Request` for anything else
+/// that is malformed
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#crud-updating) for more information
+pub fn validate_update<A>(document: &Document, expected_id: &A::Id) -> Result<(), Vec<Error>>
+where A: Attributes + Serialize + DeserializeOwned, A::Id: PartialEq {
+    let go = single_resource(&document.data)?;
+    let mut errors = Vec::new();
+
+    check_type::<A>(go, &mut errors);
+    match go.id.parse::<A::Id>() {
+        Ok(id) if id == *expected_id => (),
+        Ok(_) => errors.push(conflict(format!("Expected id {}, got {}", expected_id, go.id),
+            "/data/id")),
+        Err(e) => errors.push(bad_request(format!("Invalid id ({})", e), "/data/id")),
+    }
+    check_relationships(&go.relationships, &mut errors);
+
+    finish(errors)
+}
+
+/// Checks a request document sent to a relationship endpoint
+///
+/// The primary data must be `null` or a single resource identifier for a to-one
+/// relationship, and an array of resource identifiers for a to-many relationship
+///
+/// Produces `400 Bad Request` for anything that is malformed
+///
+/// See the [JSON:API docs](https://jsonapi.org/format/#crud-updating-relationships) for more
+/// information
+pub fn validate_relationship_update(document: &Document, to_many: bool) -> Result<(), Vec<Error>> {
+    let mut errors = Vec::new();
+    match (&document.data, to_many) {
+        (OptionalVec::NotPresent, _) =>
+            errors.push(bad_request("Request document is missing primary data".into(), "")),
+        (OptionalVec::One(one), false) => if let Some(go) = one {
+            check_identifier(go, "/data", &mut errors);
+        },
+        (OptionalVec::Many(many), true) => for (i, go) in many.iter().enumerate() {
+            check_identifier(go, &format!("/data/{}", i), &mut errors);
+        },
+        (_, true) => errors.push(bad_request(
+            "Expected an array of resource identifiers for a to-many relationship".into(),
+            "/data")),
+        (_, false) => errors.push(bad_request(
+            "Expected null or a single resource identifier for a to-one relationship".into(),
+            "/data")),
+    }
+    finish(errors)
+}
+
+fn single_resource(data: &OptionalVec<GenericObject>) -> Result<&GenericObject, Vec<Error>> {
+    match data {
+        OptionalVec::One(Some(go)) => Ok(go),
+        OptionalVec::NotPresent =>
+            Err(vec![bad_request("Request document is missing primary data".into(), "")]),
+        _ => Err(vec![bad_request("Expected a single resource object".into(), "/data")]),
+    }
+}
+
+fn check_type<A: Attributes>(go: &GenericObject, errors: &mut Vec<Error>) {
+    if go.kind != A::kind() {
+        errors.push(conflict(format!("Expected type {}, got {}", A::kind(), go.kind), "/data/type"));
+    }
+}
+
+/// Every relationship provided in a request MUST contain a `data` member
+fn check_relationships(relationships: &Option<Relationships>, errors: &mut Vec<Error>) {
+    if let Some(relationships) = relationships {
+        for (name, r) in relationships {
+            if r.data.is_not_present() {
+                errors.push(bad_request(format!("Relationship {} is missing data", name),
+                    &format!("/data/relationships/{}", escape(name))));
+            }
+        }
+    }
+}
+
+fn check_identifier(go: &GenericObject, pointer: &str, errors: &mut Vec<Error>) {
+    if go.attributes.is_some() || go.relationships.is_some() || go.links.is_some() {
+        errors.push(bad_request("Expected a resource identifier, got a resource object".into(),
+            pointer));
+    }
+}
+
+pub (crate) fn finish(errors: Vec<Error>) -> Result<(), Vec<Error>> {
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+pub (crate) fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn error(status: &str, title: &str, detail: String, pointer: &str) -> Error {
+    Error {
+        status: Some(status.into()),
+        title: Some(title.into()),
+        detail: Some(detail),
+        source: Some(ErrorSource {
+            pointer: Some(pointer.into()),
+            parameter: None,
+        }),
+        ..Default::default()
+    }
+}
+
+pub (crate) fn bad_request(detail: String, pointer: &str) -> Error {
+    error("400", "Bad Request", detail, pointer)
+}
+
+/// A `400 Bad Request` caused by a query parameter rather than the request document
+pub (crate) fn bad_parameter(detail: String, parameter: &str) -> Error {
+    Error {
+        status: Some("400".into()),
+        title: Some("Bad Request".into()),
+        detail: Some(detail),
+        source: Some(ErrorSource {
+            pointer: None,
+            parameter: Some(parameter.into()),
+        }),
+        ..Default::default()
+    }
+}
+
+fn forbidden(detail: String, pointer: &str) -> Error {
+    error("403", "Forbidden", detail, pointer)
+}
+
+fn conflict(detail: String, pointer: &str) -> Error {
+    error("409", "Conflict", detail, pointer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Identifier, Relationship};
+    use serde_derive::{Serialize, Deserialize};
+    use serde_json::{json, Value};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Attr {
+        kitty: bool,
+    }
+
+    impl Attributes for Attr {
+        type Id = String;
+        fn kind() -> String { "b".into() }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct ClientAttr;
+
+    impl Attributes for ClientAttr {
+        type Id = String;
+        fn kind() -> String { "b".into() }
+        fn client_ids() -> bool { true }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Numbered;
+
+    impl Attributes for Numbered {
+        type Id = u32;
+        fn kind() -> String { "n".into() }
+        fn client_ids() -> bool { true }
+    }
+
+    fn document(v: Value) -> Document {
+        serde_json::from_value(v).unwrap()
+    }
+
+    fn create_document(v: Value) -> CreateDocument {
+        serde_json::from_value(v).unwrap()
+    }
+
+    fn statuses(r: Result<(), Vec<Error>>) -> Vec<String> {
+        r.unwrap_err().into_iter().map(|e| e.status.unwrap()).collect()
+    }
+
+    #[test]
+    fn create() {
+        let d = create_document(json!({"data": {"type": "b", "attributes": {"kitty": true}}}));
+        assert!(validate_create::<Attr>(&d).is_ok());
+
+        let d = create_document(json!({"data": {"type": "c", "attributes": {"kitty": true}}}));
+        assert_eq!(statuses(validate_create::<Attr>(&d)), vec!["409"]);
+
+        let d = create_document(json!({"data": {"type": "b", "id": "a"}}));
+        assert_eq!(statuses(validate_create::<Attr>(&d)), vec!["403"]);
+        assert!(validate_create::<ClientAttr>(&d).is_ok());
+
+        let d = create_document(json!({"data": {"type": "n", "id": "a"}}));
+        assert_eq!(statuses(validate_create::<Numbered>(&d)), vec!["400"]);
+        let d = create_document(json!({"data": {"type": "n", "id": "01"}}));
+        assert!(validate_create::<Numbered>(&d).is_ok());
+
+        let d = create_document(json!({"data": {"type": "b", "attributes": {}}}));
+        assert_eq!(statuses(validate_create::<Attr>(&d)), vec!["400"]);
+
+        let d = create_document(json!({"data": [{"type": "b"}]}));
+        assert_eq!(statuses(validate_create::<Attr>(&d)), vec!["400"]);
+
+        let d = create_document(json!({"meta": {}}));
+        assert_eq!(statuses(validate_create::<Attr>(&d)), vec!["400"]);
+    }
+
+    #[test]
+    fn create_relationships() {
+        let mut go : GenericObject = Identifier::new("".into(), "b".into()).into();
+        let mut relationships = Relationships::new();
+        relationships.insert("a/b".into(), Relationship::default());
+        go.relationships = Some(relationships);
+        let d = CreateDocument {
+            data: OptionalVec::One(Some(go)),
+            ..Default::default()
+        };
+
+        let errors = validate_create::<Attr>(&d).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].status, Some("400".into()));
+        assert_eq!(errors[0].source.as_ref().unwrap().pointer,
+            Some("/data/relationships/a~1b".into()));
+    }
+
+    #[test]
+    fn update() {
+        let d = document(json!({"data": {"type": "b", "id": "a", "attributes": {}}}));
+        assert!(validate_update::<Attr>(&d, &"a".into()).is_ok());
+        assert_eq!(statuses(validate_update::<Attr>(&d, &"z".into())), vec!["409"]);
+
+        let d = document(json!({"data": {"type": "c", "id": "z"}}));
+        assert_eq!(statuses(validate_update::<Attr>(&d, &"a".into())), vec!["409", "409"]);
+
+        let d = document(json!({"data": [{"type": "b", "id": "a"}]}));
+        assert_eq!(statuses(validate_update::<Attr>(&d, &"a".into())), vec!["400"]);
+
+        let d = document(json!({"data": {"type": "n", "id": "01"}}));
+        assert!(validate_update::<Numbered>(&d, &1).is_ok());
+        assert_eq!(statuses(validate_update::<Numbered>(&d, &2)), vec!["409"]);
+        let d = document(json!({"data": {"type": "n", "id": "a"}}));
+        assert_eq!(statuses(validate_update::<Numbered>(&d, &1)), vec!["400"]);
+    }
+
+    #[test]
+    fn relationship_update() {
+        let d = document(json!({"data": null}));
+        assert!(validate_relationship_update(&d, false).is_ok());
+        assert_eq!(statuses(validate_relationship_update(&d, true)), vec!["400"]);
+
+        let d = document(json!({"data": {"type": "b", "id": "a"}}));
+        assert!(validate_relationship_update(&d, false).is_ok());
+        assert_eq!(statuses(validate_relationship_update(&d, true)), vec!["400"]);
+
+        let d = document(json!({"data": [{"type": "b", "id": "a"}]}));
+        assert!(validate_relationship_update(&d, true).is_ok());
+        assert_eq!(statuses(validate_relationship_update(&d, false)), vec!["400"]);
+
+        let d = document(json!({"data": [{"type": "b", "id": "a", "attributes": {}}, {"type": "b", "id": "c", "links": {}}]}));
+        assert_eq!(statuses(validate_relationship_update(&d, true)), vec!["400", "400"]);
+
+        let d = document(json!({}));
+        assert_eq!(statuses(validate_relationship_update(&d, true)), vec!["400"]);
+    }
+}
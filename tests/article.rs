@@ -3,21 +3,19 @@ use json_api::{
     Attributes, 
     ResourceObject, 
     OptionalVec, 
-    Relationships, 
     Links, 
     Link, 
     Relationship,
     GenericObject,
 };
 use serde_derive::{Serialize, Deserialize};
-use serde_json::{self, json};
-use std::convert::TryInto;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Article {
     title: String,
 }
 impl Attributes for Article { 
+    type Id = String;
     fn kind() -> String { "articles".into() } 
     fn links(id: &str) -> Option<Links> {
         let mut links = Links::new();
@@ -61,6 +59,7 @@ pub struct People {
     contact: String,
 }
 impl Attributes for People { 
+    type Id = String;
     fn kind() -> String { "people".into() } 
     fn links(id: &str) -> Option<Links> {
         let mut links = Links::new();
@@ -74,6 +73,7 @@ pub struct Comment {
     body: String,
 }
 impl Attributes for Comment { 
+    type Id = String;
     fn kind() -> String { "comments".into() } 
     fn links(id: &str) -> Option<Links> {
         let mut links = Links::new();
@@ -118,7 +118,7 @@ fn test() {
     Article::add_comments(&mut article, &comments);
 
     let mut included = Vec::new();
-    included.extend(comments.into_iter().map(|v| GenericObject::from(v)));
+    included.extend(comments.iter().map(GenericObject::from));
     included.push(author.into());
 
     let document = Document {
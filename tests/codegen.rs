@@ -0,0 +1,35 @@
+#![cfg(all(feature = "codegen", not(feature = "raw-attributes")))]
+
+use json_api::{Attributes, Codegen, Document, OptionalVec, ResourceObject};
+use std::convert::TryFrom;
+
+mod generated {
+    include!("codegen/article.rs");
+}
+
+const DOCUMENT : &str = include_str!("article.json");
+
+#[test]
+fn up_to_date() {
+    let mut codegen = Codegen::new();
+    codegen.add_file(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/article.json")).unwrap();
+    assert_eq!(codegen.generate(), include_str!("codegen/article.rs"));
+}
+
+#[test]
+fn generated_types() {
+    let doc : Document = serde_json::from_str(DOCUMENT).unwrap();
+    let article = match doc.data {
+        OptionalVec::One(Some(go)) => ResourceObject::<generated::Articles>::try_from(go).unwrap(),
+        _ => panic!("expected a single article"),
+    };
+    assert_eq!(article.attributes.unwrap().title, "JSON:API is kind of strange in rust");
+    assert_eq!(article.links, generated::Articles::links("1"));
+
+    let included = doc.included.unwrap();
+    let comment = ResourceObject::<generated::Comments>::try_from(included[0].clone()).unwrap();
+    assert_eq!(comment.attributes.unwrap().body, "First!");
+    let person = ResourceObject::<generated::People>::try_from(included[2].clone()).unwrap();
+    assert_eq!(person.attributes.unwrap().first_name, "kitty");
+    assert!(ResourceObject::<generated::People>::try_from(included[0].clone()).is_err());
+}
@@ -0,0 +1,78 @@
+// Generated by json-api codegen, do not edit
+
+/// Attributes of `articles` resources
+#[derive(::serde_derive::Serialize, ::serde_derive::Deserialize, Debug, Clone, PartialEq)]
+pub struct Articles {
+    pub title: String,
+}
+
+impl ::json_api::Attributes for Articles {
+    type Id = String;
+
+    fn kind() -> String {
+        "articles".into()
+    }
+
+    fn links(id: &str) -> Option<::json_api::Links> {
+        let mut links = ::json_api::Links::new();
+        links.insert("self".into(), ::json_api::Link::Url(format!("/articles/{}", id)));
+        Some(links)
+    }
+
+    fn relationships() -> Vec<(String, ::json_api::RelationshipSchema)> {
+        vec![
+            ("author".into(), ::json_api::RelationshipSchema::to_one("people".into())),
+            ("comments".into(), ::json_api::RelationshipSchema::to_many("comments".into())),
+        ]
+    }
+}
+
+/// Attributes of `comments` resources
+#[derive(::serde_derive::Serialize, ::serde_derive::Deserialize, Debug, Clone, PartialEq)]
+pub struct Comments {
+    pub body: String,
+}
+
+impl ::json_api::Attributes for Comments {
+    type Id = String;
+
+    fn kind() -> String {
+        "comments".into()
+    }
+
+    fn links(id: &str) -> Option<::json_api::Links> {
+        let mut links = ::json_api::Links::new();
+        links.insert("self".into(), ::json_api::Link::Url(format!("/comments/{}", id)));
+        Some(links)
+    }
+
+    fn relationships() -> Vec<(String, ::json_api::RelationshipSchema)> {
+        vec![
+            ("author".into(), ::json_api::RelationshipSchema::to_one("people".into())),
+        ]
+    }
+}
+
+/// Attributes of `people` resources
+#[derive(::serde_derive::Serialize, ::serde_derive::Deserialize, Debug, Clone, PartialEq)]
+pub struct People {
+    pub contact: String,
+    #[serde(rename = "first-name")]
+    pub first_name: String,
+    #[serde(rename = "last-name")]
+    pub last_name: String,
+}
+
+impl ::json_api::Attributes for People {
+    type Id = String;
+
+    fn kind() -> String {
+        "people".into()
+    }
+
+    fn links(id: &str) -> Option<::json_api::Links> {
+        let mut links = ::json_api::Links::new();
+        links.insert("self".into(), ::json_api::Link::Url(format!("/people/{}", id)));
+        Some(links)
+    }
+}
@@ -0,0 +1,131 @@
+// Unparsed attributes can only be written back out as JSON
+#![cfg(not(feature = "raw-attributes"))]
+
+use json_api::{
+    Document,
+    RelationshipDocument,
+    Identifier,
+    OptionalVec,
+    Error,
+    ErrorSource,
+    Meta,
+    Attributes,
+    ResourceObject,
+};
+use serde::{ser::Serialize, de::DeserializeOwned};
+use serde_derive::{Serialize, Deserialize};
+use serde_json::json;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+const DOCUMENT : &str = include_str!("article.json");
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct Article {
+    title: String,
+}
+impl Attributes for Article {
+    type Id = String;
+    fn kind() -> String { "articles".into() }
+}
+
+fn article() -> Document {
+    serde_json::from_str(DOCUMENT).unwrap()
+}
+
+fn relationship() -> RelationshipDocument {
+    RelationshipDocument {
+        data: OptionalVec::Many(vec![
+            Identifier::new("1".into(), "people".into()),
+            Identifier::new("2".into(), "people".into()),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn errors() -> Document {
+    let mut meta = Meta::new();
+    meta.insert("count".into(), json!(-1));
+    meta.insert("ratio".into(), json!(0.5));
+    meta.insert("nested".into(), json!({"a": [true, null, "b"]}));
+    Document {
+        errors: Some(vec![Error {
+            status: Some("422".into()),
+            source: Some(ErrorSource {
+                pointer: Some("/data/attributes/title".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }]),
+        meta: Some(meta),
+        ..Default::default()
+    }
+}
+
+fn empty() -> Document {
+    Document {
+        data: OptionalVec::One(None),
+        ..Default::default()
+    }
+}
+
+mod cbor {
+    use super::*;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(t: &T) {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(t, &mut bytes).unwrap();
+        let t2 : T = ciborium::de::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(t, &t2);
+    }
+
+    #[test]
+    fn documents() {
+        round_trip(&article());
+        round_trip(&relationship());
+        round_trip(&errors());
+        round_trip(&empty());
+    }
+
+    #[test]
+    fn typed() {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&article(), &mut bytes).unwrap();
+        let d : Document = ciborium::de::from_reader(bytes.as_slice()).unwrap();
+        let a = match d.data {
+            OptionalVec::One(Some(a)) => ResourceObject::<Article>::try_from(a).unwrap(),
+            _ => panic!("expected a single resource"),
+        };
+        assert_eq!(a.attributes.unwrap().title, "JSON:API is kind of strange in rust");
+    }
+}
+
+mod msgpack {
+    use super::*;
+
+    // Structs have to be written as maps since optional members are skipped
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(t: &T) {
+        let bytes = rmp_serde::to_vec_named(t).unwrap();
+        let t2 : T = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(t, &t2);
+    }
+
+    #[test]
+    fn documents() {
+        round_trip(&article());
+        round_trip(&relationship());
+        round_trip(&errors());
+        round_trip(&empty());
+    }
+
+    #[test]
+    fn typed() {
+        let bytes = rmp_serde::to_vec_named(&article()).unwrap();
+        let d : Document = rmp_serde::from_slice(&bytes).unwrap();
+        let a = match d.data {
+            OptionalVec::One(Some(a)) => ResourceObject::<Article>::try_from(a).unwrap(),
+            _ => panic!("expected a single resource"),
+        };
+        assert_eq!(a.attributes.unwrap().title, "JSON:API is kind of strange in rust");
+    }
+}